@@ -0,0 +1,142 @@
+use std::{borrow::Cow, sync::OnceLock};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ezql_core::{
+    dialects::{Dialect, SqliteDialect},
+    prelude::*,
+    queries::{OrderBy, SelectQueryParams, WhereClause},
+    Backend, SqliteBackend,
+};
+
+struct User {
+    id: Option<i32>,
+    name: Option<String>,
+    email: Option<String>,
+    is_active: Option<bool>,
+}
+
+impl EzqlModelTrait for User {
+    fn get_table() -> Table {
+        Table {
+            name: "users".to_string(),
+            columns: vec![
+                Column {
+                    name: "id".to_string(),
+                    data_type: EzqlType::Integer(),
+                    properties: vec![ColumnProperty::PrimaryKey],
+                },
+                Column {
+                    name: "name".to_string(),
+                    data_type: EzqlType::VarChar(255),
+                    properties: vec![ColumnProperty::NotNull],
+                },
+                Column {
+                    name: "email".to_string(),
+                    data_type: EzqlType::VarChar(255),
+                    properties: vec![ColumnProperty::Unique],
+                },
+                Column {
+                    name: "is_active".to_string(),
+                    data_type: EzqlType::Boolean(),
+                    properties: vec![ColumnProperty::default(true)],
+                },
+            ],
+        }
+    }
+
+    // Built once and shared by all operations
+    fn table() -> Cow<'static, Table> {
+        static TABLE: OnceLock<Table> = OnceLock::new();
+        Cow::Borrowed(TABLE.get_or_init(Self::get_table))
+    }
+
+    fn as_column_values(&self) -> Vec<Option<EzqlValue>> {
+        vec![
+            self.id.as_ref().map(ToEzql::to_ezql),
+            self.name.as_ref().map(ToEzql::to_ezql),
+            self.email.as_ref().map(ToEzql::to_ezql),
+            self.is_active.as_ref().map(ToEzql::to_ezql),
+        ]
+    }
+
+    fn from_column_values(
+        values: Vec<Option<EzqlValue>>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self {
+            id: decode_column(&values, 0)?,
+            name: decode_column(&values, 1)?,
+            email: decode_column(&values, 2)?,
+            is_active: decode_column(&values, 3)?,
+        })
+    }
+}
+
+// Multi-row select, ids are shifted so params change between calls
+fn active_users(from_id: i32) -> SelectQueryParams {
+    SelectQueryParams {
+        where_clause: Some(WhereClause::And(vec![
+            WhereClause::Ge("id".to_string(), from_id.into()),
+            WhereClause::Eq("is_active".to_string(), true.into()),
+        ])),
+        order_by: vec![OrderBy::asc("id")],
+        limit: Some(50),
+        ..SelectQueryParams::default()
+    }
+}
+
+// ====< Table rebuilt per call vs cached per model >====
+fn bench_table(c: &mut Criterion) {
+    let mut group = c.benchmark_group("table");
+    group.bench_function("get_table", |b| b.iter(|| black_box(User::get_table())));
+    group.bench_function("cached", |b| b.iter(|| black_box(User::table())));
+    group.finish();
+}
+
+// ====< Table and SQL built per call vs cached, 50 rows per select >====
+fn bench_select(c: &mut Criterion) {
+    let backend = SqliteBackend::new_in_memory();
+    ModelBackend::create_table::<User>(&backend, false).unwrap();
+    let users = (0..1000)
+        .map(|i| User {
+            id: None,
+            name: Some(format!("user{}", i)),
+            email: Some(format!("user{}@example.com", i)),
+            is_active: Some(i % 2 == 0),
+        })
+        .collect::<Vec<User>>();
+    ModelBackend::insert::<User>(&backend, &users.iter().collect::<Vec<&User>>()).unwrap();
+
+    let mut group = c.benchmark_group("select");
+    group.bench_function("generate_sql", |b| {
+        let mut id = 0;
+        b.iter(|| {
+            id = id % 100 + 1;
+            black_box(SqliteDialect::select(&User::get_table(), active_users(id)))
+        })
+    });
+    group.bench_function("uncached", |b| {
+        let mut id = 0;
+        b.iter(|| {
+            id = id % 100 + 1;
+            let table = User::get_table();
+            let rows = Backend::select(&backend, &table, active_users(id)).unwrap();
+            black_box(
+                rows.iter()
+                    .map(|row| User::from_row(row, &table))
+                    .collect::<Result<Vec<User>, _>>()
+                    .unwrap(),
+            )
+        })
+    });
+    group.bench_function("cached", |b| {
+        let mut id = 0;
+        b.iter(|| {
+            id = id % 100 + 1;
+            black_box(ModelBackend::select::<User>(&backend, active_users(id)).unwrap())
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_table, bench_select);
+criterion_main!(benches);
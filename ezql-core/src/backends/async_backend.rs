@@ -0,0 +1,295 @@
+use std::future::Future;
+
+use crate::{
+    dialects::Dialect,
+    prelude::{EzqlModelTrait, EzqlRecordTrait, EzqlValue, Row, Table},
+    queries::{
+        CompoundSelect, Keyset, KeysetPage, OnConflict, Page, RawParams, SelectQueryParams,
+        UpdateQueryParams,
+    },
+};
+
+use super::BulkInsertProgress;
+
+// ====< Error returned across threads, same as sync backend errors >====
+pub type AsyncError = Box<dyn std::error::Error + Send + Sync>;
+
+// ====< Async backend trait >====
+pub trait AsyncBackend<D>
+where
+    D: Dialect,
+{
+    // ====< Close connections >====
+    fn close(self) -> impl Future<Output = Result<(), AsyncError>> + Send;
+
+    // ====< Create table >====
+    fn create_table(
+        &self,
+        if_not_exists: bool,
+        table: Table,
+    ) -> impl Future<Output = Result<(), AsyncError>> + Send;
+
+    // ====< Drop table >====
+    fn drop_table(
+        &self,
+        if_exists: bool,
+        table: Table,
+    ) -> impl Future<Output = Result<(), AsyncError>> + Send;
+
+    // ====< Introspect table >====
+    fn introspect_table(
+        &self,
+        name: &str,
+    ) -> impl Future<Output = Result<Table, AsyncError>> + Send;
+
+    // ====< Insert, returns generated keys >====
+    fn insert(
+        &self,
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+    ) -> impl Future<Output = Result<Vec<EzqlValue>, AsyncError>> + Send;
+
+    // ====< Insert, returns inserted rows >====
+    fn insert_returning(
+        &self,
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+    ) -> impl Future<Output = Result<Vec<Vec<Option<EzqlValue>>>, AsyncError>> + Send;
+
+    // ====< Bulk insert in chunks, returns inserted rows count >====
+    // Progress is reported from the blocking thread
+    fn insert_bulk<F>(
+        &self,
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+        on_progress: F,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send
+    where
+        F: FnMut(BulkInsertProgress) + Send + 'static;
+
+    // ====< Upsert >====
+    fn upsert(
+        &self,
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+        conflict: Vec<String>,
+        on_conflict: OnConflict,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send;
+
+    // ====< Select rows with named columns >====
+    fn select(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> impl Future<Output = Result<Vec<Row>, AsyncError>> + Send;
+
+    // ====< Select values in projection order >====
+    fn select_values(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> impl Future<Output = Result<Vec<Vec<Option<EzqlValue>>>, AsyncError>> + Send;
+
+    // ====< Select into ad-hoc records >====
+    fn select_as<R>(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> impl Future<Output = Result<Vec<R>, AsyncError>> + Send
+    where
+        R: EzqlRecordTrait + Send + 'static;
+
+    // ====< Compound select, values in projection order >====
+    fn select_compound(
+        &self,
+        compound: CompoundSelect,
+    ) -> impl Future<Output = Result<Vec<Vec<Option<EzqlValue>>>, AsyncError>> + Send;
+
+    // ====< Count >====
+    fn count(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send;
+
+    // ====< Delete >====
+    fn delete(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send;
+
+    // ====< Delete, returns deleted rows >====
+    fn delete_returning(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> impl Future<Output = Result<Vec<Vec<Option<EzqlValue>>>, AsyncError>> + Send;
+
+    // ====< Update >====
+    fn update(
+        &self,
+        table: &Table,
+        query: UpdateQueryParams,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send;
+
+    // ====< Update, returns updated rows >====
+    fn update_returning(
+        &self,
+        table: &Table,
+        query: UpdateQueryParams,
+    ) -> impl Future<Output = Result<Vec<Vec<Option<EzqlValue>>>, AsyncError>> + Send;
+
+    // ====< Execute raw SQL, returns affected rows count >====
+    fn execute_raw(
+        &self,
+        sql: &str,
+        params: RawParams,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send;
+
+    // ====< Query raw SQL >====
+    fn query_raw(
+        &self,
+        sql: &str,
+        params: RawParams,
+    ) -> impl Future<Output = Result<Vec<Row>, AsyncError>> + Send;
+}
+
+// ====< Async model backend trait >====
+pub trait AsyncModelBackend<D>
+where
+    D: Dialect,
+{
+    // ====< Create table >====
+    fn create_table<M>(
+        &self,
+        if_not_exists: bool,
+    ) -> impl Future<Output = Result<(), AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static;
+
+    // ====< Drop table >====
+    fn drop_table<M>(&self, if_exists: bool) -> impl Future<Output = Result<(), AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static;
+
+    // ====< Insert, returns generated keys >====
+    fn insert<M>(
+        &self,
+        models: &[&M],
+    ) -> impl Future<Output = Result<Vec<EzqlValue>, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static;
+
+    // ====< Insert, returns inserted models >====
+    fn insert_returning<M>(
+        &self,
+        models: &[&M],
+    ) -> impl Future<Output = Result<Vec<M>, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static;
+
+    // ====< Bulk insert in chunks, returns inserted rows count >====
+    fn insert_bulk<M, F>(
+        &self,
+        models: &[&M],
+        on_progress: F,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static,
+        F: FnMut(BulkInsertProgress) + Send + 'static;
+
+    // ====< Upsert >====
+    fn upsert<M>(
+        &self,
+        models: &[&M],
+        conflict: Vec<String>,
+        on_conflict: OnConflict,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static;
+
+    // ====< Select >====
+    fn select<M>(
+        &self,
+        query: SelectQueryParams,
+    ) -> impl Future<Output = Result<Vec<M>, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static;
+
+    // ====< Compound select of model rows >====
+    fn select_compound<M>(
+        &self,
+        compound: CompoundSelect,
+    ) -> impl Future<Output = Result<Vec<M>, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static;
+
+    // ====< Count >====
+    fn count<M>(
+        &self,
+        query: SelectQueryParams,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static;
+
+    // ====< Offset pagination, pages start at 1 >====
+    fn paginate<M>(
+        &self,
+        query: SelectQueryParams,
+        page: usize,
+        per_page: usize,
+    ) -> impl Future<Output = Result<Page<M>, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static;
+
+    // ====< Keyset pagination >====
+    fn paginate_keyset<M>(
+        &self,
+        query: SelectQueryParams,
+        keyset: Keyset,
+    ) -> impl Future<Output = Result<KeysetPage<M>, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static;
+
+    // ====< Delete >====
+    fn delete<M>(
+        &self,
+        query: SelectQueryParams,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static;
+
+    // ====< Delete, returns deleted models >====
+    fn delete_returning<M>(
+        &self,
+        query: SelectQueryParams,
+    ) -> impl Future<Output = Result<Vec<M>, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static;
+
+    // ====< Update >====
+    fn update<M>(
+        &self,
+        query: UpdateQueryParams,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static;
+
+    // ====< Update, returns updated models >====
+    fn update_returning<M>(
+        &self,
+        query: UpdateQueryParams,
+    ) -> impl Future<Output = Result<Vec<M>, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static;
+
+    // ====< Query raw SQL into models >====
+    fn query_as<M>(
+        &self,
+        sql: &str,
+        params: RawParams,
+    ) -> impl Future<Output = Result<Vec<M>, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static;
+}
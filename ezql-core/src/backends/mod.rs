@@ -1,11 +1,31 @@
 use crate::{
     dialects::Dialect,
-    prelude::{EzqlModelTrait, EzqlValue, Table},
-    queries::{SelectQueryParams, UpdateQueryParams},
+    prelude::{EzqlModelTrait, EzqlRecordTrait, EzqlValue, Row, Table},
+    queries::{
+        CompoundSelect, Keyset, KeysetPage, OnConflict, Page, RawParams, SelectQueryParams,
+        UpdateQueryParams,
+    },
 };
 
+#[cfg(feature = "async")]
+pub mod async_backend;
+#[cfg(all(feature = "sqlite", feature = "async"))]
+pub mod sqlite_async;
 #[cfg(feature = "sqlite")]
 pub mod sqlite_backend;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_pool;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_stream;
+
+// ====< Bulk insert progress >====
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BulkInsertProgress {
+    pub chunk: usize,
+    pub chunks: usize,
+    pub rows_inserted: usize,
+    pub total_rows: usize,
+}
 
 // ====< Backend trait >====
 pub trait Backend<D>
@@ -13,45 +33,136 @@ where
     D: Dialect,
 {
     // ====< Close connection >====
-    fn close(self) -> Result<(), Box<dyn std::error::Error>>;
+    fn close(self) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
 
     // ====< Create table >====
     fn create_table(
         &self,
         if_not_exists: bool,
         table: Table,
-    ) -> Result<(), Box<dyn std::error::Error>>;
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
 
     // ====< Drop table >====
-    fn drop_table(&self, if_exists: bool, table: Table) -> Result<(), Box<dyn std::error::Error>>;
+    fn drop_table(
+        &self,
+        if_exists: bool,
+        table: Table,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
 
-    // ====< Insert >====
+    // ====< Introspect table >====
+    fn introspect_table(
+        &self,
+        name: &str,
+    ) -> Result<Table, Box<dyn std::error::Error + Send + Sync>>;
+
+    // ====< Insert, returns generated keys >====
     fn insert(
         &self,
         table: &Table,
         models: Vec<Vec<Option<EzqlValue>>>,
-    ) -> Result<(), Box<dyn std::error::Error>>;
+    ) -> Result<Vec<EzqlValue>, Box<dyn std::error::Error + Send + Sync>>;
 
-    // ====< Select >====
+    // ====< Insert, returns inserted rows >====
+    fn insert_returning(
+        &self,
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+    ) -> Result<Vec<Vec<Option<EzqlValue>>>, Box<dyn std::error::Error + Send + Sync>>;
+
+    // ====< Bulk insert in chunks, returns inserted rows count >====
+    fn insert_bulk(
+        &self,
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+        on_progress: &mut dyn FnMut(BulkInsertProgress),
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>;
+
+    // ====< Upsert >====
+    fn upsert(
+        &self,
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+        conflict: Vec<String>,
+        on_conflict: OnConflict,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>;
+
+    // ====< Select rows with named columns >====
     fn select(
         &self,
         table: &Table,
         query: SelectQueryParams,
-    ) -> Result<Vec<Vec<Option<EzqlValue>>>, Box<dyn std::error::Error>>;
+    ) -> Result<Vec<Row>, Box<dyn std::error::Error + Send + Sync>>;
+
+    // ====< Select values in projection order >====
+    fn select_values(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> Result<Vec<Vec<Option<EzqlValue>>>, Box<dyn std::error::Error + Send + Sync>>;
+
+    // ====< Select into ad-hoc records >====
+    fn select_as<R>(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> Result<Vec<R>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        R: EzqlRecordTrait;
+
+    // ====< Compound select, values in projection order >====
+    fn select_compound(
+        &self,
+        compound: CompoundSelect,
+    ) -> Result<Vec<Vec<Option<EzqlValue>>>, Box<dyn std::error::Error + Send + Sync>>;
+
+    // ====< Count >====
+    fn count(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>;
 
     // ====< Delete >====
     fn delete(
         &self,
         table: &Table,
         query: SelectQueryParams,
-    ) -> Result<usize, Box<dyn std::error::Error>>;
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>;
+
+    // ====< Delete, returns deleted rows >====
+    fn delete_returning(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> Result<Vec<Vec<Option<EzqlValue>>>, Box<dyn std::error::Error + Send + Sync>>;
 
     // ====< Update >====
     fn update(
         &self,
         table: &Table,
         query: UpdateQueryParams,
-    ) -> Result<usize, Box<dyn std::error::Error>>;
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>;
+
+    // ====< Update, returns updated rows >====
+    fn update_returning(
+        &self,
+        table: &Table,
+        query: UpdateQueryParams,
+    ) -> Result<Vec<Vec<Option<EzqlValue>>>, Box<dyn std::error::Error + Send + Sync>>;
+
+    // ====< Execute raw SQL, returns affected rows count >====
+    fn execute_raw(
+        &self,
+        sql: &str,
+        params: RawParams,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>;
+
+    // ====< Query raw SQL >====
+    fn query_raw(
+        &self,
+        sql: &str,
+        params: RawParams,
+    ) -> Result<Vec<Row>, Box<dyn std::error::Error + Send + Sync>>;
 }
 
 // ====< Model backend trait >====
@@ -60,32 +171,137 @@ where
     D: Dialect,
 {
     // ====< Create table >====
-    fn create_table<M>(&self, if_not_exists: bool) -> Result<(), Box<dyn std::error::Error>>
+    fn create_table<M>(
+        &self,
+        if_not_exists: bool,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
     where
         M: EzqlModelTrait;
 
     // ====< Drop table >====
-    fn drop_table<M>(&self, if_exists: bool) -> Result<(), Box<dyn std::error::Error>>
+    fn drop_table<M>(
+        &self,
+        if_exists: bool,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
     where
         M: EzqlModelTrait;
 
-    // ====< Insert >====
-    fn insert<M>(&self, models: &[&M]) -> Result<(), Box<dyn std::error::Error>>
+    // ====< Insert, returns generated keys >====
+    fn insert<M>(
+        &self,
+        models: &[&M],
+    ) -> Result<Vec<EzqlValue>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait;
+
+    // ====< Insert, returns inserted models >====
+    fn insert_returning<M>(
+        &self,
+        models: &[&M],
+    ) -> Result<Vec<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait;
+
+    // ====< Bulk insert in chunks, returns inserted rows count >====
+    fn insert_bulk<M>(
+        &self,
+        models: &[&M],
+        on_progress: &mut dyn FnMut(BulkInsertProgress),
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait;
+
+    // ====< Upsert >====
+    fn upsert<M>(
+        &self,
+        models: &[&M],
+        conflict: Vec<String>,
+        on_conflict: OnConflict,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>
     where
         M: EzqlModelTrait;
 
     // ====< Select >====
-    fn select<M>(&self, query: SelectQueryParams) -> Result<Vec<M>, Box<dyn std::error::Error>>
+    fn select<M>(
+        &self,
+        query: SelectQueryParams,
+    ) -> Result<Vec<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait;
+
+    // ====< Compound select of model rows >====
+    fn select_compound<M>(
+        &self,
+        compound: CompoundSelect,
+    ) -> Result<Vec<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait;
+
+    // ====< Count >====
+    fn count<M>(
+        &self,
+        query: SelectQueryParams,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait;
+
+    // ====< Offset pagination, pages start at 1 >====
+    fn paginate<M>(
+        &self,
+        query: SelectQueryParams,
+        page: usize,
+        per_page: usize,
+    ) -> Result<Page<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait;
+
+    // ====< Keyset pagination >====
+    fn paginate_keyset<M>(
+        &self,
+        query: SelectQueryParams,
+        keyset: Keyset,
+    ) -> Result<KeysetPage<M>, Box<dyn std::error::Error + Send + Sync>>
     where
         M: EzqlModelTrait;
 
     // ====< Delete >====
-    fn delete<M>(&self, query: SelectQueryParams) -> Result<usize, Box<dyn std::error::Error>>
+    fn delete<M>(
+        &self,
+        query: SelectQueryParams,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait;
+
+    // ====< Delete, returns deleted models >====
+    fn delete_returning<M>(
+        &self,
+        query: SelectQueryParams,
+    ) -> Result<Vec<M>, Box<dyn std::error::Error + Send + Sync>>
     where
         M: EzqlModelTrait;
 
     // ====< Update >====
-    fn update<M>(&self, query: UpdateQueryParams) -> Result<usize, Box<dyn std::error::Error>>
+    fn update<M>(
+        &self,
+        query: UpdateQueryParams,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait;
+
+    // ====< Update, returns updated models >====
+    fn update_returning<M>(
+        &self,
+        query: UpdateQueryParams,
+    ) -> Result<Vec<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait;
+
+    // ====< Query raw SQL into models >====
+    fn query_as<M>(
+        &self,
+        sql: &str,
+        params: RawParams,
+    ) -> Result<Vec<M>, Box<dyn std::error::Error + Send + Sync>>
     where
         M: EzqlModelTrait;
 }
@@ -0,0 +1,419 @@
+use std::{future::Future, sync::Arc};
+
+use crate::{
+    components::model::decode_models,
+    dialects::SqliteDialect,
+    prelude::{EzqlModelTrait, EzqlRecordTrait, EzqlValue, Row, Table},
+    queries::{
+        CompoundSelect, Keyset, KeysetPage, OnConflict, Page, RawParams, SelectQueryParams,
+        UpdateQueryParams,
+    },
+};
+
+use super::{
+    async_backend::{AsyncBackend, AsyncError, AsyncModelBackend},
+    sqlite_backend::SqliteBackend,
+    sqlite_pool::SqlitePool,
+    Backend, BulkInsertProgress, ModelBackend,
+};
+
+// ====< Async SQLite backend >====
+// Operations run on tokio's blocking thread pool, one pooled connection each
+#[derive(Clone)]
+pub struct AsyncSqliteBackend {
+    pool: Arc<SqlitePool>,
+}
+
+impl AsyncSqliteBackend {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool: Arc::new(pool),
+        }
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    // ====< Run closure in transaction on single connection >====
+    pub async fn transaction<T, F>(&self, f: F) -> Result<T, AsyncError>
+    where
+        F: FnOnce(&SqliteBackend) -> Result<T, Box<dyn std::error::Error + Send + Sync>>
+            + Send
+            + 'static,
+        T: Send + 'static,
+    {
+        self.run(move |pool| pool.transaction(f)).await
+    }
+
+    // ====< Run blocking operation on pool >====
+    fn run<T, F>(&self, f: F) -> impl Future<Output = Result<T, AsyncError>> + Send
+    where
+        F: FnOnce(&SqlitePool) -> Result<T, AsyncError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let pool = self.pool.clone();
+        async move { tokio::task::spawn_blocking(move || f(&pool)).await? }
+    }
+}
+
+fn column_values<M>(models: &[&M]) -> Vec<Vec<Option<EzqlValue>>>
+where
+    M: EzqlModelTrait,
+{
+    models.iter().map(|m| m.as_column_values()).collect()
+}
+
+// ====< Async SQLite backend trait implementation >====
+impl AsyncBackend<SqliteDialect> for AsyncSqliteBackend {
+    // ====< Close connections, fails while clones are alive >====
+    async fn close(self) -> Result<(), AsyncError> {
+        let pool = Arc::try_unwrap(self.pool)
+            .map_err(|_| "Backend is still shared, can not close connections")?;
+        tokio::task::spawn_blocking(move || Backend::close(pool)).await?
+    }
+
+    fn create_table(
+        &self,
+        if_not_exists: bool,
+        table: Table,
+    ) -> impl Future<Output = Result<(), AsyncError>> + Send {
+        self.run(move |pool| Backend::create_table(pool, if_not_exists, table))
+    }
+
+    fn drop_table(
+        &self,
+        if_exists: bool,
+        table: Table,
+    ) -> impl Future<Output = Result<(), AsyncError>> + Send {
+        self.run(move |pool| Backend::drop_table(pool, if_exists, table))
+    }
+
+    fn introspect_table(
+        &self,
+        name: &str,
+    ) -> impl Future<Output = Result<Table, AsyncError>> + Send {
+        let name = name.to_string();
+        self.run(move |pool| pool.introspect_table(&name))
+    }
+
+    fn insert(
+        &self,
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+    ) -> impl Future<Output = Result<Vec<EzqlValue>, AsyncError>> + Send {
+        let table = table.clone();
+        self.run(move |pool| Backend::insert(pool, &table, models))
+    }
+
+    fn insert_returning(
+        &self,
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+    ) -> impl Future<Output = Result<Vec<Vec<Option<EzqlValue>>>, AsyncError>> + Send {
+        let table = table.clone();
+        self.run(move |pool| Backend::insert_returning(pool, &table, models))
+    }
+
+    fn insert_bulk<F>(
+        &self,
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+        mut on_progress: F,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send
+    where
+        F: FnMut(BulkInsertProgress) + Send + 'static,
+    {
+        let table = table.clone();
+        self.run(move |pool| Backend::insert_bulk(pool, &table, models, &mut on_progress))
+    }
+
+    fn upsert(
+        &self,
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+        conflict: Vec<String>,
+        on_conflict: OnConflict,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send {
+        let table = table.clone();
+        self.run(move |pool| Backend::upsert(pool, &table, models, conflict, on_conflict))
+    }
+
+    fn select(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> impl Future<Output = Result<Vec<Row>, AsyncError>> + Send {
+        let table = table.clone();
+        self.run(move |pool| Backend::select(pool, &table, query))
+    }
+
+    fn select_values(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> impl Future<Output = Result<Vec<Vec<Option<EzqlValue>>>, AsyncError>> + Send {
+        let table = table.clone();
+        self.run(move |pool| pool.select_values(&table, query))
+    }
+
+    fn select_as<R>(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> impl Future<Output = Result<Vec<R>, AsyncError>> + Send
+    where
+        R: EzqlRecordTrait + Send + 'static,
+    {
+        let table = table.clone();
+        self.run(move |pool| pool.select_as(&table, query))
+    }
+
+    fn select_compound(
+        &self,
+        compound: CompoundSelect,
+    ) -> impl Future<Output = Result<Vec<Vec<Option<EzqlValue>>>, AsyncError>> + Send {
+        self.run(move |pool| Backend::select_compound(pool, compound))
+    }
+
+    fn count(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send {
+        let table = table.clone();
+        self.run(move |pool| Backend::count(pool, &table, query))
+    }
+
+    fn delete(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send {
+        let table = table.clone();
+        self.run(move |pool| Backend::delete(pool, &table, query))
+    }
+
+    fn delete_returning(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> impl Future<Output = Result<Vec<Vec<Option<EzqlValue>>>, AsyncError>> + Send {
+        let table = table.clone();
+        self.run(move |pool| Backend::delete_returning(pool, &table, query))
+    }
+
+    fn update(
+        &self,
+        table: &Table,
+        query: UpdateQueryParams,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send {
+        let table = table.clone();
+        self.run(move |pool| Backend::update(pool, &table, query))
+    }
+
+    fn update_returning(
+        &self,
+        table: &Table,
+        query: UpdateQueryParams,
+    ) -> impl Future<Output = Result<Vec<Vec<Option<EzqlValue>>>, AsyncError>> + Send {
+        let table = table.clone();
+        self.run(move |pool| Backend::update_returning(pool, &table, query))
+    }
+
+    fn execute_raw(
+        &self,
+        sql: &str,
+        params: RawParams,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send {
+        let sql = sql.to_string();
+        self.run(move |pool| pool.execute_raw(&sql, params))
+    }
+
+    fn query_raw(
+        &self,
+        sql: &str,
+        params: RawParams,
+    ) -> impl Future<Output = Result<Vec<Row>, AsyncError>> + Send {
+        let sql = sql.to_string();
+        self.run(move |pool| pool.query_raw(&sql, params))
+    }
+}
+
+// ====< Async SQLite model backend trait implementation >====
+// Models are read into values before leaving the calling task
+impl AsyncModelBackend<SqliteDialect> for AsyncSqliteBackend {
+    fn create_table<M>(
+        &self,
+        if_not_exists: bool,
+    ) -> impl Future<Output = Result<(), AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static,
+    {
+        self.run(move |pool| ModelBackend::create_table::<M>(pool, if_not_exists))
+    }
+
+    fn drop_table<M>(&self, if_exists: bool) -> impl Future<Output = Result<(), AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static,
+    {
+        self.run(move |pool| ModelBackend::drop_table::<M>(pool, if_exists))
+    }
+
+    fn insert<M>(
+        &self,
+        models: &[&M],
+    ) -> impl Future<Output = Result<Vec<EzqlValue>, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static,
+    {
+        let values = column_values(models);
+        self.run(move |pool| Backend::insert(pool, &M::table(), values))
+    }
+
+    fn insert_returning<M>(
+        &self,
+        models: &[&M],
+    ) -> impl Future<Output = Result<Vec<M>, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static,
+    {
+        let values = column_values(models);
+        self.run(move |pool| {
+            let table = &M::table();
+            let rows = Backend::insert_returning(pool, table, values)?;
+            decode_models(table, rows)
+        })
+    }
+
+    fn insert_bulk<M, F>(
+        &self,
+        models: &[&M],
+        mut on_progress: F,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static,
+        F: FnMut(BulkInsertProgress) + Send + 'static,
+    {
+        let values = column_values(models);
+        self.run(move |pool| Backend::insert_bulk(pool, &M::table(), values, &mut on_progress))
+    }
+
+    fn upsert<M>(
+        &self,
+        models: &[&M],
+        conflict: Vec<String>,
+        on_conflict: OnConflict,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static,
+    {
+        let values = column_values(models);
+        self.run(move |pool| Backend::upsert(pool, &M::table(), values, conflict, on_conflict))
+    }
+
+    fn select<M>(
+        &self,
+        query: SelectQueryParams,
+    ) -> impl Future<Output = Result<Vec<M>, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static,
+    {
+        self.run(move |pool| ModelBackend::select(pool, query))
+    }
+
+    fn select_compound<M>(
+        &self,
+        compound: CompoundSelect,
+    ) -> impl Future<Output = Result<Vec<M>, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static,
+    {
+        self.run(move |pool| ModelBackend::select_compound(pool, compound))
+    }
+
+    fn count<M>(
+        &self,
+        query: SelectQueryParams,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static,
+    {
+        self.run(move |pool| ModelBackend::count::<M>(pool, query))
+    }
+
+    fn paginate<M>(
+        &self,
+        query: SelectQueryParams,
+        page: usize,
+        per_page: usize,
+    ) -> impl Future<Output = Result<Page<M>, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static,
+    {
+        self.run(move |pool| ModelBackend::paginate(pool, query, page, per_page))
+    }
+
+    fn paginate_keyset<M>(
+        &self,
+        query: SelectQueryParams,
+        keyset: Keyset,
+    ) -> impl Future<Output = Result<KeysetPage<M>, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static,
+    {
+        self.run(move |pool| ModelBackend::paginate_keyset(pool, query, keyset))
+    }
+
+    fn delete<M>(
+        &self,
+        query: SelectQueryParams,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static,
+    {
+        self.run(move |pool| ModelBackend::delete::<M>(pool, query))
+    }
+
+    fn delete_returning<M>(
+        &self,
+        query: SelectQueryParams,
+    ) -> impl Future<Output = Result<Vec<M>, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static,
+    {
+        self.run(move |pool| ModelBackend::delete_returning(pool, query))
+    }
+
+    fn update<M>(
+        &self,
+        query: UpdateQueryParams,
+    ) -> impl Future<Output = Result<usize, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static,
+    {
+        self.run(move |pool| ModelBackend::update::<M>(pool, query))
+    }
+
+    fn update_returning<M>(
+        &self,
+        query: UpdateQueryParams,
+    ) -> impl Future<Output = Result<Vec<M>, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static,
+    {
+        self.run(move |pool| ModelBackend::update_returning(pool, query))
+    }
+
+    fn query_as<M>(
+        &self,
+        sql: &str,
+        params: RawParams,
+    ) -> impl Future<Output = Result<Vec<M>, AsyncError>> + Send
+    where
+        M: EzqlModelTrait + Send + 'static,
+    {
+        let sql = sql.to_string();
+        self.run(move |pool| ModelBackend::query_as(pool, &sql, params))
+    }
+}
@@ -1,24 +1,113 @@
-use rusqlite::Connection;
+use std::{cell::RefCell, collections::VecDeque, sync::Arc};
+
+use regex::Regex;
+use rusqlite::{functions::FunctionFlags, types::ValueRef, CachedStatement, Connection};
 
 use crate::{
+    components::{
+        model::{decode_model_rows, decode_models, decode_records},
+        row::RowColumn,
+        sql_cache::{memoized, Shape, Statement},
+    },
     dialects::{Dialect, SqliteDialect},
-    prelude::{EzqlModelTrait, EzqlValue, Table},
-    queries::{SelectQueryParams, UpdateQueryParams},
+    prelude::{Column, ColumnProperty, EzqlModelTrait, EzqlRecordTrait, EzqlValue, Row, Table},
+    queries::{
+        CompoundSelect, Keyset, KeysetPage, OnConflict, OrderBy, Page, Query, RawParams,
+        SelectQueryParams, Subquery, UpdateQueryParams, WhereClause,
+    },
+    types::{EzqlType, FromEzql, ToEzql},
+    utils::split_top_level,
 };
 
-use super::{Backend, ModelBackend};
+use super::{sqlite_stream::SelectStatement, Backend, BulkInsertProgress, ModelBackend};
 
 // ====< SQLite backend >====
 #[cfg(feature = "sqlite")]
 pub struct SqliteBackend {
     connection: rusqlite::Connection,
+    statements: RefCell<StatementCache>,
+}
+
+// Prepared statements kept per connection unless configured otherwise
+#[cfg(feature = "sqlite")]
+pub const DEFAULT_STATEMENT_CACHE_CAPACITY: usize = 64;
+
+// ====< Statement cache statistics >====
+#[cfg(feature = "sqlite")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StatementCacheStats {
+    pub hits: usize,
+    pub misses: usize,
+    pub size: usize,
+    pub capacity: usize,
+}
+
+// ====< LRU bookkeeping for rusqlite's statement cache >====
+// rusqlite keeps the statements, this mirrors its keys to count hits and misses
+#[cfg(feature = "sqlite")]
+struct StatementCache {
+    keys: VecDeque<String>,
+    capacity: usize,
+    hits: usize,
+    misses: usize,
+}
+
+#[cfg(feature = "sqlite")]
+impl StatementCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            keys: VecDeque::new(),
+            capacity,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    // Mark SQL as most recently used
+    fn touch(&mut self, sql: &str) {
+        // rusqlite keys statements by trimmed SQL
+        let sql = sql.trim();
+        match self.keys.iter().position(|k| k == sql) {
+            Some(index) => {
+                self.hits += 1;
+                let key = self.keys.remove(index).unwrap();
+                self.keys.push_back(key);
+            }
+            None => {
+                self.misses += 1;
+                self.keys.push_back(sql.to_string());
+                self.trim();
+            }
+        }
+    }
+
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.trim();
+    }
+
+    // Evict least recently used keys over capacity
+    fn trim(&mut self) {
+        while self.keys.len() > self.capacity {
+            self.keys.pop_front();
+        }
+    }
+
+    fn stats(&self) -> StatementCacheStats {
+        StatementCacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            size: self.keys.len(),
+            capacity: self.capacity,
+        }
+    }
 }
 
 // ====< SQLite backend trait implementation >====
 #[cfg(feature = "sqlite")]
 impl Backend<SqliteDialect> for SqliteBackend {
     // ====< Close connection >====
-    fn close(self) -> Result<(), Box<dyn std::error::Error>> {
+    fn close(self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         if self.connection.close().is_err() {
             return Err("Failed to close connection".into());
         }
@@ -30,66 +119,277 @@ impl Backend<SqliteDialect> for SqliteBackend {
         &self,
         if_not_exists: bool,
         table: Table,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let query = SqliteDialect::create_table(if_not_exists, table);
-        self.connection.execute(&query.sql, [])?;
+        self.prepare(&query.sql)?.execute([])?;
         Ok(())
     }
 
     // ====< Drop table >====
-    fn drop_table(&self, if_exists: bool, table: Table) -> Result<(), Box<dyn std::error::Error>> {
+    fn drop_table(
+        &self,
+        if_exists: bool,
+        table: Table,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let query = SqliteDialect::drop_table(if_exists, table);
-        self.connection.execute(&query.sql, [])?;
+        self.prepare(&query.sql)?.execute([])?;
         Ok(())
     }
 
-    // ====< Insert >====
+    // ====< Introspect table >====
+    fn introspect_table(
+        &self,
+        name: &str,
+    ) -> Result<Table, Box<dyn std::error::Error + Send + Sync>> {
+        // Constraints are not exposed by pragmas, so read them from the schema
+        let create_sql: String = self
+            .prepare("SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?;")?
+            .query_row([name], |row| row.get(0))?;
+        let definitions = SqliteDialect::column_definitions(&create_sql);
+
+        // Single column UNIQUE constraints are backed by automatic indexes
+        let unique: Vec<String> = self
+            .prepare(
+                "SELECT min(ii.name) FROM pragma_index_list(?) AS il, pragma_index_info(il.name) AS ii \
+                 WHERE il.\"unique\" AND il.origin = 'u' GROUP BY il.name HAVING count(*) = 1;",
+            )?
+            .query_map([name], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        let mut stmt = self.prepare(
+            "SELECT name, type, \"notnull\", dflt_value, pk, hidden FROM pragma_table_xinfo(?);",
+        )?;
+        let mut rows = stmt.query([name])?;
+        let mut columns = Vec::new();
+        while let Some(row) = rows.next()? {
+            let column_name: String = row.get(0)?;
+            let declared_type: String = row.get(1)?;
+            let definition = definitions
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(&column_name))
+                .map(|(_, d)| d.as_str())
+                .unwrap_or("");
+
+            // Enums are stored as text restricted by CHECK constraint
+            let mut checks = SqliteDialect::clause_expressions(definition, "CHECK");
+            let variants = checks.iter().position(|check| {
+                declared_type.eq_ignore_ascii_case("TEXT")
+                    && check.starts_with(&format!("{} IN", column_name))
+            });
+            let data_type = match variants {
+                Some(i) => {
+                    let check = checks.remove(i);
+                    let list = check[column_name.len() + 3..].trim();
+                    EzqlType::Enum {
+                        name: column_name.clone(),
+                        variants: split_top_level(
+                            list.trim_start_matches('(').trim_end_matches(')'),
+                            ',',
+                        )
+                        .into_iter()
+                        .filter_map(SqliteDialect::parse_value)
+                        .map(|v| String::from_ezql(v).unwrap_or_default())
+                        .collect(),
+                    }
+                }
+                None => SqliteDialect::parse_type(&declared_type).ok_or(format!(
+                    "Unsupported type {} of column {}",
+                    declared_type, column_name
+                ))?,
+            };
+
+            // Collect properties
+            let mut properties = Vec::new();
+            if row.get::<_, i32>(4)? > 0 {
+                properties.push(ColumnProperty::PrimaryKey);
+            }
+            if row.get(2)? {
+                properties.push(ColumnProperty::NotNull);
+            }
+            if unique.contains(&column_name) {
+                properties.push(ColumnProperty::Unique);
+            }
+            if let Some(default) = row
+                .get::<_, Option<String>>(3)?
+                .and_then(|d| SqliteDialect::parse_value(&d))
+            {
+                properties.push(ColumnProperty::Default(match (&data_type, default) {
+                    (EzqlType::Enum { .. }, EzqlValue::VarChar(v)) => EzqlValue::Enum(v),
+                    (_, default) => default,
+                }));
+            }
+            properties.extend(checks.into_iter().map(ColumnProperty::Check));
+
+            // Hidden column is 2 for virtual and 3 for stored generated columns
+            let hidden: i32 = row.get(5)?;
+            if hidden == 2 || hidden == 3 {
+                properties.push(ColumnProperty::Generated {
+                    expr: SqliteDialect::clause_expressions(definition, "GENERATED ALWAYS AS")
+                        .pop()
+                        .unwrap_or_default(),
+                    stored: hidden == 3,
+                });
+            }
+
+            columns.push(Column {
+                name: column_name,
+                data_type,
+                properties,
+            });
+        }
+
+        Ok(Table {
+            name: name.to_string(),
+            columns,
+        })
+    }
+
+    // ====< Insert, returns generated keys >====
     fn insert(
         &self,
         table: &Table,
         models: Vec<Vec<Option<EzqlValue>>>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let query = SqliteDialect::insert(table, models);
-        self.connection.execute(
-            &query.sql,
-            rusqlite::params_from_iter(query.params.as_slice()),
-        )?;
-        Ok(())
+    ) -> Result<Vec<EzqlValue>, Box<dyn std::error::Error + Send + Sync>> {
+        if models.is_empty() {
+            return Ok(vec![]);
+        }
+        if !self.supports_returning() {
+            return self.insert_rowids(table, models);
+        }
+
+        let query = SqliteDialect::returning(
+            SqliteDialect::insert(table, models),
+            Some(vec![Self::key_column(table)]),
+        );
+        Ok(self
+            .query_rows(&query)?
+            .into_iter()
+            .filter_map(|mut row| row.remove(0))
+            .collect())
     }
 
-    // ====< Select >====
+    // ====< Insert, returns inserted rows >====
+    fn insert_returning(
+        &self,
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+    ) -> Result<Vec<Vec<Option<EzqlValue>>>, Box<dyn std::error::Error + Send + Sync>> {
+        if models.is_empty() {
+            return Ok(vec![]);
+        }
+        if !self.supports_returning() {
+            return self.insert_returning_fallback(table, models);
+        }
+
+        let query = SqliteDialect::returning(SqliteDialect::insert(table, models), None);
+        self.query_rows(&query)
+    }
+
+    // ====< Bulk insert in chunks, returns inserted rows count >====
+    fn insert_bulk(
+        &self,
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+        on_progress: &mut dyn FnMut(BulkInsertProgress),
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        // Every row binds at most one parameter per column
+        let chunk_size = (SqliteDialect::max_params() / table.columns.len().max(1)).max(1);
+        let total_rows = models.len();
+        let chunks = total_rows.div_ceil(chunk_size);
+
+        // Insert all chunks atomically, also inside an open transaction
+        let savepoint = self.savepoint()?;
+        let mut rows_inserted = 0;
+        for (chunk, rows) in models.chunks(chunk_size).enumerate() {
+            let query = SqliteDialect::insert(table, rows.to_vec());
+
+            // Chunks of the same shape share one prepared statement
+            let mut stmt = self.prepare(&query.sql)?;
+            rows_inserted += stmt.execute(rusqlite::params_from_iter(query.params.as_slice()))?;
+
+            on_progress(BulkInsertProgress {
+                chunk: chunk + 1,
+                chunks,
+                rows_inserted,
+                total_rows,
+            });
+        }
+        savepoint.release()?;
+
+        Ok(rows_inserted)
+    }
+
+    // ====< Upsert >====
+    fn upsert(
+        &self,
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+        conflict: Vec<String>,
+        on_conflict: OnConflict,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        if models.is_empty() {
+            return Ok(0);
+        }
+        // DO UPDATE needs conflict target
+        if conflict.is_empty() && !matches!(on_conflict, OnConflict::DoNothing) {
+            return Err("Upsert without conflict columns can only do nothing".into());
+        }
+        let query = SqliteDialect::upsert(table, models, conflict, on_conflict);
+        Ok(self
+            .prepare(&query.sql)?
+            .execute(rusqlite::params_from_iter(query.params.as_slice()))?)
+    }
+
+    // ====< Select rows with named columns >====
     fn select(
         &self,
         table: &Table,
         query: SelectQueryParams,
-    ) -> Result<Vec<Vec<Option<EzqlValue>>>, Box<dyn std::error::Error>> {
-        let select_query = SqliteDialect::select(table, query.clone());
-        let mut stmt = self.connection.prepare(&select_query.sql)?;
-        let mut rows = stmt.query(rusqlite::params_from_iter(select_query.params.as_slice()))?;
-        let mut result = Vec::new();
-        let has_columns = query.columns.is_some();
-        while let Some(row) = rows.next()? {
-            let mut row_result = Vec::new();
-            let mut idx = 0;
-            for i in 0..table.columns.len() {
-                // skip if column is not selected
-                if has_columns
-                    && !query
-                        .columns
-                        .as_ref()
-                        .unwrap()
-                        .contains(&table.columns[i].name)
-                {
-                    row_result.push(None);
-                    continue;
-                }
-                // Select value
-                row_result.push(row.get(idx)?);
-                idx += 1;
-            }
-            result.push(row_result);
-        }
-        Ok(result)
+    ) -> Result<Vec<Row>, Box<dyn std::error::Error + Send + Sync>> {
+        self.select_rows(&SqliteDialect::select(table, query))
+    }
+
+    // ====< Select values in projection order >====
+    fn select_values(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> Result<Vec<Vec<Option<EzqlValue>>>, Box<dyn std::error::Error + Send + Sync>> {
+        self.query_rows(&SqliteDialect::select(table, query))
+    }
+
+    // ====< Select into ad-hoc records >====
+    fn select_as<R>(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> Result<Vec<R>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        R: EzqlRecordTrait,
+    {
+        let columns: Vec<String> = match &query.columns {
+            Some(columns) => columns.iter().map(|c| c.name().to_string()).collect(),
+            None => table.columns.iter().map(|c| c.name.clone()).collect(),
+        };
+        let rows = self.select_values(table, query)?;
+        decode_records(&columns, rows)
+    }
+
+    // ====< Compound select, values in projection order >====
+    fn select_compound(
+        &self,
+        compound: CompoundSelect,
+    ) -> Result<Vec<Vec<Option<EzqlValue>>>, Box<dyn std::error::Error + Send + Sync>> {
+        self.query_rows(&SqliteDialect::compound_select(compound))
+    }
+
+    // ====< Count >====
+    fn count(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        self.count_rows(&SqliteDialect::count(table, query))
     }
 
     // ====< Delete >====
@@ -97,12 +397,40 @@ impl Backend<SqliteDialect> for SqliteBackend {
         &self,
         table: &Table,
         query: SelectQueryParams,
-    ) -> Result<usize, Box<dyn std::error::Error>> {
-        let delete_query = SqliteDialect::delete(table, query);
-        Ok(self.connection.execute(
-            &delete_query.sql,
-            rusqlite::params_from_iter(delete_query.params.as_slice()),
-        )?)
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        self.execute(&SqliteDialect::delete(table, query))
+    }
+
+    // ====< Delete, returns deleted rows >====
+    fn delete_returning(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> Result<Vec<Vec<Option<EzqlValue>>>, Box<dyn std::error::Error + Send + Sync>> {
+        if !self.supports_returning() {
+            return self.delete_returning_fallback(table, query);
+        }
+
+        // DELETE ignores limit and offset, so delete only rowids of limited select
+        let query = if query.limit.is_some() || query.offset.is_some() {
+            SelectQueryParams {
+                where_clause: Some(WhereClause::InSubquery(
+                    "rowid".to_string(),
+                    Box::new(Subquery::new(
+                        table.clone(),
+                        SelectQueryParams {
+                            columns: Some(vec!["rowid".into()]),
+                            ..query
+                        },
+                    )),
+                )),
+                ..SelectQueryParams::default()
+            }
+        } else {
+            query
+        };
+        let delete_query = SqliteDialect::returning(SqliteDialect::delete(table, query), None);
+        self.query_rows(&delete_query)
     }
 
     // ====< Update >====
@@ -110,12 +438,61 @@ impl Backend<SqliteDialect> for SqliteBackend {
         &self,
         table: &Table,
         query: UpdateQueryParams,
-    ) -> Result<usize, Box<dyn std::error::Error>> {
-        let update_query = SqliteDialect::update(table, query);
-        Ok(self.connection.execute(
-            &update_query.sql,
-            rusqlite::params_from_iter(update_query.params.as_slice()),
-        )?)
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        Self::check_assignments(table, &query)?;
+        self.execute(&SqliteDialect::update(table, query))
+    }
+
+    // ====< Update, returns updated rows >====
+    fn update_returning(
+        &self,
+        table: &Table,
+        query: UpdateQueryParams,
+    ) -> Result<Vec<Vec<Option<EzqlValue>>>, Box<dyn std::error::Error + Send + Sync>> {
+        Self::check_assignments(table, &query)?;
+        if !self.supports_returning() {
+            return self.update_returning_fallback(table, query);
+        }
+
+        let update_query = SqliteDialect::returning(SqliteDialect::update(table, query), None);
+        self.query_rows(&update_query)
+    }
+
+    // ====< Execute raw SQL, returns affected rows count >====
+    fn execute_raw(
+        &self,
+        sql: &str,
+        params: RawParams,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let mut stmt = self.prepare(sql)?;
+        let count = match &params {
+            RawParams::Positional(values) => {
+                stmt.execute(rusqlite::params_from_iter(values.as_slice()))?
+            }
+            RawParams::Named(values) => stmt.execute(named_params(values).as_slice())?,
+        };
+        Ok(count)
+    }
+
+    // ====< Query raw SQL >====
+    fn query_raw(
+        &self,
+        sql: &str,
+        params: RawParams,
+    ) -> Result<Vec<Row>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut stmt = self.prepare(sql)?;
+        let columns = row_columns(&stmt);
+        let mut rows = match &params {
+            RawParams::Positional(values) => {
+                stmt.query(rusqlite::params_from_iter(values.as_slice()))?
+            }
+            RawParams::Named(values) => stmt.query(named_params(values).as_slice())?,
+        };
+        let mut result = Vec::new();
+        while let Some(row) = rows.next()? {
+            result.push(read_row(row, &columns)?);
+        }
+        Ok(result)
     }
 }
 
@@ -123,330 +500,4224 @@ impl Backend<SqliteDialect> for SqliteBackend {
 #[cfg(feature = "sqlite")]
 impl SqliteBackend {
     pub fn new(path: &str) -> Self {
-        Self {
-            connection: Connection::open(path).unwrap(),
-        }
+        Self::open(path).unwrap()
     }
 
     pub fn new_in_memory() -> Self {
-        Self {
-            connection: Connection::open_in_memory().unwrap(),
-        }
+        Self::from_connection(Connection::open_in_memory().unwrap()).unwrap()
     }
-}
 
-// ====< SQlite model backend trait implementation >====
-#[cfg(feature = "sqlite")]
-impl ModelBackend<SqliteDialect> for SqliteBackend {
-    // ====< Create table >====
-    fn create_table<M>(&self, if_not_exists: bool) -> Result<(), Box<dyn std::error::Error>>
-    where
-        M: EzqlModelTrait,
-    {
-        Backend::create_table(self, if_not_exists, M::get_table())
+    // Fallible open, used by the pool
+    pub(super) fn open(path: &str) -> rusqlite::Result<Self> {
+        Self::from_connection(Connection::open(path)?)
     }
 
-    // ====< Drop table >====
-    fn drop_table<M>(&self, if_exists: bool) -> Result<(), Box<dyn std::error::Error>>
-    where
-        M: EzqlModelTrait,
-    {
-        Backend::drop_table(self, if_exists, M::get_table())
+    fn from_connection(connection: Connection) -> rusqlite::Result<Self> {
+        Self::register_functions(&connection)?;
+        connection.set_prepared_statement_cache_capacity(DEFAULT_STATEMENT_CACHE_CAPACITY);
+        Ok(Self {
+            connection,
+            statements: RefCell::new(StatementCache::new(DEFAULT_STATEMENT_CACHE_CAPACITY)),
+        })
     }
 
-    // ====< Insert >====
-    fn insert<M>(&self, models: &[&M]) -> Result<(), Box<dyn std::error::Error>>
-    where
-        M: EzqlModelTrait,
-    {
-        let table = M::get_table();
-        let values = models
-            .iter()
-            .map(|m| m.as_column_values())
-            .collect::<Vec<Vec<Option<EzqlValue>>>>();
-        Backend::insert(self, &table, values)
+    // ====< Wait for locks held by other connections >====
+    pub fn set_busy_timeout(
+        &self,
+        timeout: std::time::Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.connection.busy_timeout(timeout)?)
     }
 
-    // ====< Select >====
-    fn select<M>(&self, query: SelectQueryParams) -> Result<Vec<M>, Box<dyn std::error::Error>>
-    where
-        M: EzqlModelTrait,
-    {
-        let table = M::get_table();
-        let values = Backend::select(self, &table, query)?;
-        let mut result = Vec::new();
-        for value in values {
-            println!("{:?}", value);
-            result.push(M::from_column_values(value)?);
-        }
-        Ok(result)
+    pub(super) fn is_autocommit(&self) -> bool {
+        self.connection.is_autocommit()
     }
 
-    // ====< Delete >====
-    fn delete<M>(&self, query: SelectQueryParams) -> Result<usize, Box<dyn std::error::Error>>
-    where
-        M: EzqlModelTrait,
-    {
-        let table = M::get_table();
-        Backend::delete(self, &table, query)
+    // Connection still answers queries
+    pub(super) fn is_healthy(&self) -> bool {
+        self.connection
+            .query_row("SELECT 1;", [], |row| row.get::<_, i64>(0))
+            .is_ok()
     }
 
-    // ====< Update >====
-    fn update<M>(&self, query: UpdateQueryParams) -> Result<usize, Box<dyn std::error::Error>>
-    where
-        M: EzqlModelTrait,
-    {
-        let table = M::get_table();
-        Backend::update(self, &table, query)
+    // ====< Prepare statement through LRU cache >====
+    fn prepare(&self, sql: &str) -> rusqlite::Result<CachedStatement<'_>> {
+        // Failed statements are not cached, so they are not counted either
+        let stmt = self.connection.prepare_cached(sql)?;
+        self.statements.borrow_mut().touch(sql);
+        Ok(stmt)
     }
-}
 
-// ====< Impl ToSql for EzqlValue >====
-#[cfg(feature = "sqlite")]
-impl rusqlite::types::ToSql for EzqlValue {
-    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput> {
-        match self {
-            EzqlValue::Integer(i) => Ok(rusqlite::types::ToSqlOutput::from(*i)),
-            EzqlValue::VarChar(s) => Ok(rusqlite::types::ToSqlOutput::from(s.as_str())),
-            EzqlValue::Boolean(b) => Ok(rusqlite::types::ToSqlOutput::from(*b)),
-            EzqlValue::Null() => Ok(rusqlite::types::ToSqlOutput::from(rusqlite::types::Null)),
-        }
+    // ====< Statement cache configuration >====
+    // Capacity 0 disables caching, every statement is compiled again
+    pub fn set_statement_cache_capacity(&self, capacity: usize) {
+        self.connection
+            .set_prepared_statement_cache_capacity(capacity);
+        self.statements.borrow_mut().set_capacity(capacity);
     }
-}
 
-// ====< Impl FromSql for EzqlValue >====
-#[cfg(feature = "sqlite")]
-impl rusqlite::types::FromSql for EzqlValue {
-    fn column_result(value: rusqlite::types::ValueRef) -> rusqlite::types::FromSqlResult<Self> {
-        match value {
-            rusqlite::types::ValueRef::Integer(i) => Ok(EzqlValue::from(i as i32)),
-            rusqlite::types::ValueRef::Text(s) => {
-                Ok(EzqlValue::from(String::from_utf8(s.to_vec()).unwrap()))
-            }
-            rusqlite::types::ValueRef::Null => Ok(EzqlValue::Null()),
-            _ => Err(rusqlite::types::FromSqlError::InvalidType),
-        }
+    pub fn statement_cache_stats(&self) -> StatementCacheStats {
+        self.statements.borrow().stats()
     }
-}
 
-// ========< TESTS >========
-#[cfg(test)]
-mod tests {
-    use crate::{
-        backends,
-        prelude::{Column, ColumnProperty, EzqlType},
-        queries::WhereClause,
-    };
+    // Finalize cached statements, statistics are kept
+    pub fn clear_statement_cache(&self) {
+        self.connection.flush_prepared_statement_cache();
+        self.statements.borrow_mut().keys.clear();
+    }
 
-    use super::*;
+    // ====< Register SQL functions missing in SQLite >====
+    fn register_functions(connection: &Connection) -> rusqlite::Result<()> {
+        // `x REGEXP y` calls regexp(y, x), compiled pattern is cached by SQLite
+        connection.create_scalar_function(
+            "regexp",
+            2,
+            FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+            |ctx| {
+                let regex: Arc<Regex> = ctx.get_or_create_aux(0, |pattern| {
+                    Regex::new(pattern.as_str()?)
+                        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
+                })?;
+                // NULL never matches
+                Ok(match ctx.get_raw(1) {
+                    ValueRef::Null => false,
+                    value => regex.is_match(
+                        value
+                            .as_str()
+                            .map_err(|e| rusqlite::Error::UserFunctionError(e.into()))?,
+                    ),
+                })
+            },
+        )
+    }
 
-    struct User {
-        id: Option<i32>,
-        name: Option<String>,
-        is_active: Option<bool>,
+    // ====< Prepare select for streaming models >====
+    pub fn select_iter<M>(
+        &self,
+        query: SelectQueryParams,
+    ) -> Result<SelectStatement<'_, M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        let table = &M::table();
+        let select_query = SqliteDialect::select(table, query);
+        let statement = self.prepare(&select_query.sql)?;
+        Ok(SelectStatement::new(
+            statement,
+            select_query.params,
+            table.clone().into_owned(),
+        ))
     }
 
-    impl EzqlModelTrait for User {
-        fn get_table() -> Table {
-            Table {
-                name: "users".to_string(),
-                columns: vec![
-                    Column {
-                        name: "id".to_string(),
-                        data_type: EzqlType::Integer(),
-                        properties: vec![ColumnProperty::PrimaryKey],
-                    },
-                    Column {
-                        name: "name".to_string(),
-                        data_type: EzqlType::VarChar(255),
-                        properties: vec![ColumnProperty::NotNull],
-                    },
-                    Column {
-                        name: "is_active".to_string(),
-                        data_type: EzqlType::Boolean(),
-                        properties: vec![ColumnProperty::default(false)],
-                    },
-                ],
-            }
-        }
+    // Savepoints nest, unlike BEGIN which fails inside a transaction
+    fn savepoint(&self) -> rusqlite::Result<Savepoint<'_>> {
+        Savepoint::new(&self.connection)
+    }
 
-        fn as_column_values(&self) -> Vec<Option<EzqlValue>> {
-            vec![
-                self.id.map(EzqlValue::Integer),
-                self.name.as_ref().map(|v| EzqlValue::VarChar(v.clone())),
-                self.is_active.map(EzqlValue::Boolean),
-            ]
+    // RETURNING clause is available since SQLite 3.35
+    pub fn supports_returning(&self) -> bool {
+        rusqlite::version_number() >= 3_035_000
+    }
+
+    // Single primary key column or rowid
+    fn key_column(table: &Table) -> String {
+        let mut keys = table.columns.iter().filter(|c| c.is_primary_key());
+        match (keys.next(), keys.next()) {
+            (Some(key), None) => key.name.clone(),
+            _ => "rowid".to_string(),
         }
+    }
 
-        fn from_column_values(
-            values: Vec<Option<EzqlValue>>,
-        ) -> Result<Self, Box<dyn std::error::Error>> {
-            Ok(Self {
-                id: values[0].as_ref().map(|v| v.into()),
-                name: values[1].as_ref().map(|v| v.into()),
-                is_active: values[2].as_ref().map(|v| v.into()),
-            })
+    // Generated columns are skipped, so at least one other column must be set
+    fn check_assignments(
+        table: &Table,
+        query: &UpdateQueryParams,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let writable = query.set.iter().any(|(name, _)| {
+            !table
+                .columns
+                .iter()
+                .any(|c| c.name == *name && c.is_generated())
+        });
+        if !writable {
+            return Err(format!("No writable columns to update in table {}", table.name).into());
         }
+        Ok(())
     }
 
-    #[test]
+    // Execute query, returns affected rows count
+    fn execute(&self, query: &Query) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self
+            .prepare(&query.sql)?
+            .execute(rusqlite::params_from_iter(query.params.as_slice()))?)
+    }
+
+    // Execute select and collect named rows
+    fn select_rows(
+        &self,
+        query: &Query,
+    ) -> Result<Vec<Row>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut stmt = self.prepare(&query.sql)?;
+        let columns = row_columns(&stmt);
+        let mut rows = stmt.query(rusqlite::params_from_iter(query.params.as_slice()))?;
+        let mut result = Vec::new();
+        while let Some(row) = rows.next()? {
+            result.push(read_row(row, &columns)?);
+        }
+        Ok(result)
+    }
+
+    // Execute count query
+    fn count_rows(&self, query: &Query) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let count: i64 = self
+            .prepare(&query.sql)?
+            .query_row(rusqlite::params_from_iter(query.params.as_slice()), |row| {
+                row.get(0)
+            })?;
+        Ok(count as usize)
+    }
+
+    // Select rows of model table with memoized SQL
+    fn select_model_rows(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> Result<Vec<Row>, Box<dyn std::error::Error + Send + Sync>> {
+        let shape = Shape::select(&query);
+        let select_query = memoized::<SqliteDialect>(Statement::Select, table, shape, || {
+            SqliteDialect::select(table, query)
+        });
+        self.select_rows(&select_query)
+    }
+
+    // Count rows of model table with memoized SQL
+    fn count_models(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let shape = Shape::select(&query);
+        let count_query = memoized::<SqliteDialect>(Statement::Count, table, shape, || {
+            SqliteDialect::count(table, query)
+        });
+        self.count_rows(&count_query)
+    }
+
+    // Execute query and collect all returned columns
+    fn query_rows(
+        &self,
+        query: &Query,
+    ) -> Result<Vec<Vec<Option<EzqlValue>>>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut stmt = self.prepare(&query.sql)?;
+        let column_count = stmt.column_count();
+        let mut rows = stmt.query(rusqlite::params_from_iter(query.params.as_slice()))?;
+        let mut result = Vec::new();
+        while let Some(row) = rows.next()? {
+            let mut row_result = Vec::new();
+            for idx in 0..column_count {
+                row_result.push(
+                    row.get::<_, Option<EzqlValue>>(idx)?
+                        .filter(|v| *v != EzqlValue::Null()),
+                );
+            }
+            result.push(row_result);
+        }
+        Ok(result)
+    }
+
+    // Insert rows one by one and collect their rowids
+    fn insert_rowids(
+        &self,
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+    ) -> Result<Vec<EzqlValue>, Box<dyn std::error::Error + Send + Sync>> {
+        let savepoint = self.savepoint()?;
+        let mut keys = Vec::new();
+        for model in models {
+            let query = SqliteDialect::insert(table, vec![model]);
+            self.prepare(&query.sql)?
+                .execute(rusqlite::params_from_iter(query.params.as_slice()))?;
+            let rowid = self.connection.last_insert_rowid();
+            let key = i32::try_from(rowid)
+                .map_err(|_| format!("Rowid {} does not fit into Integer", rowid))?;
+            keys.push(EzqlValue::Integer(key));
+        }
+        savepoint.release()?;
+        Ok(keys)
+    }
+
+    // Select rows matching where clause and delete them
+    fn delete_returning_fallback(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> Result<Vec<Vec<Option<EzqlValue>>>, Box<dyn std::error::Error + Send + Sync>> {
+        // Rowid is selected first, followed by table columns
+        let columns = std::iter::once("rowid")
+            .chain(table.columns.iter().map(|c| c.name.as_str()))
+            .map(Into::into)
+            .collect();
+        let savepoint = self.savepoint()?;
+        let mut rowids = Vec::new();
+        let rows = self
+            .query_rows(&SqliteDialect::select(
+                table,
+                SelectQueryParams {
+                    columns: Some(columns),
+                    ..query
+                },
+            ))?
+            .into_iter()
+            .map(|mut row| {
+                rowids.extend(row.remove(0));
+                row
+            })
+            .collect();
+
+        // Delete exactly the selected rows, limit and offset included
+        Backend::delete(
+            self,
+            table,
+            SelectQueryParams {
+                where_clause: Some(WhereClause::In("rowid".to_string(), rowids)),
+                ..SelectQueryParams::default()
+            },
+        )?;
+        savepoint.release()?;
+        Ok(rows)
+    }
+
+    // Remember rowids of matching rows, update them and select them back
+    fn update_returning_fallback(
+        &self,
+        table: &Table,
+        query: UpdateQueryParams,
+    ) -> Result<Vec<Vec<Option<EzqlValue>>>, Box<dyn std::error::Error + Send + Sync>> {
+        let savepoint = self.savepoint()?;
+        let rowids = self
+            .query_rows(&SqliteDialect::select(
+                table,
+                SelectQueryParams {
+                    columns: Some(vec!["rowid".into()]),
+                    where_clause: query.where_clause.clone(),
+                    ..SelectQueryParams::default()
+                },
+            ))?
+            .into_iter()
+            .filter_map(|mut row| row.remove(0))
+            .collect();
+        Backend::update(self, table, query)?;
+        let rows = Backend::select(
+            self,
+            table,
+            SelectQueryParams {
+                where_clause: Some(WhereClause::In("rowid".to_string(), rowids)),
+                order_by: vec![OrderBy::asc("rowid")],
+                ..SelectQueryParams::default()
+            },
+        )?;
+        savepoint.release()?;
+        Ok(rows.iter().map(|row| row.values_for(table)).collect())
+    }
+
+    // Insert rows and select them back by rowid
+    fn insert_returning_fallback(
+        &self,
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+    ) -> Result<Vec<Vec<Option<EzqlValue>>>, Box<dyn std::error::Error + Send + Sync>> {
+        let keys = self.insert_rowids(table, models)?;
+        let rows = Backend::select(
+            self,
+            table,
+            SelectQueryParams {
+                where_clause: Some(WhereClause::In("rowid".to_string(), keys)),
+                order_by: vec![OrderBy::asc("rowid")],
+                ..SelectQueryParams::default()
+            },
+        )?;
+        Ok(rows.iter().map(|row| row.values_for(table)).collect())
+    }
+}
+
+// ====< Savepoint rolled back unless released >====
+struct Savepoint<'conn> {
+    connection: &'conn Connection,
+    released: bool,
+}
+
+impl<'conn> Savepoint<'conn> {
+    fn new(connection: &'conn Connection) -> rusqlite::Result<Self> {
+        connection.execute_batch("SAVEPOINT ezql;")?;
+        Ok(Self {
+            connection,
+            released: false,
+        })
+    }
+
+    fn release(mut self) -> rusqlite::Result<()> {
+        self.connection.execute_batch("RELEASE ezql;")?;
+        self.released = true;
+        Ok(())
+    }
+}
+
+impl Drop for Savepoint<'_> {
+    fn drop(&mut self) {
+        if !self.released {
+            // Fails only if SQLite already rolled back the transaction
+            let _ = self
+                .connection
+                .execute_batch("ROLLBACK TO ezql; RELEASE ezql;");
+        }
+    }
+}
+
+// ====< Named parameters for binding >====
+fn named_params(values: &[(String, EzqlValue)]) -> Vec<(&str, &dyn rusqlite::ToSql)> {
+    values
+        .iter()
+        .map(|(name, value)| (name.as_str(), value as &dyn rusqlite::ToSql))
+        .collect()
+}
+
+// ====< Result column names and declared types >====
+pub(super) fn row_columns(stmt: &rusqlite::Statement) -> Arc<[RowColumn]> {
+    stmt.columns()
+        .iter()
+        .map(|c| RowColumn::new(c.name(), c.decl_type().and_then(SqliteDialect::parse_type)))
+        .collect()
+}
+
+// ====< Read row with named columns >====
+pub(super) fn read_row(row: &rusqlite::Row, columns: &Arc<[RowColumn]>) -> rusqlite::Result<Row> {
+    let mut values = Vec::with_capacity(columns.len());
+    for idx in 0..columns.len() {
+        // NULL is represented as missing value
+        values.push(
+            row.get::<_, Option<EzqlValue>>(idx)?
+                .filter(|v| *v != EzqlValue::Null()),
+        );
+    }
+    Ok(Row::new(columns.clone(), values))
+}
+
+// ====< SQlite model backend trait implementation >====
+#[cfg(feature = "sqlite")]
+impl ModelBackend<SqliteDialect> for SqliteBackend {
+    // ====< Create table >====
+    fn create_table<M>(
+        &self,
+        if_not_exists: bool,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        Backend::create_table(self, if_not_exists, M::table().into_owned())
+    }
+
+    // ====< Drop table >====
+    fn drop_table<M>(&self, if_exists: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        Backend::drop_table(self, if_exists, M::table().into_owned())
+    }
+
+    // ====< Insert, returns generated keys >====
+    fn insert<M>(
+        &self,
+        models: &[&M],
+    ) -> Result<Vec<EzqlValue>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        let table = &M::table();
+        let values = models
+            .iter()
+            .map(|m| m.as_column_values())
+            .collect::<Vec<Vec<Option<EzqlValue>>>>();
+        Backend::insert(self, table, values)
+    }
+
+    // ====< Insert, returns inserted models >====
+    fn insert_returning<M>(
+        &self,
+        models: &[&M],
+    ) -> Result<Vec<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        let table = &M::table();
+        let values = models
+            .iter()
+            .map(|m| m.as_column_values())
+            .collect::<Vec<Vec<Option<EzqlValue>>>>();
+        let rows = Backend::insert_returning(self, table, values)?;
+        decode_models(table, rows)
+    }
+
+    // ====< Bulk insert in chunks, returns inserted rows count >====
+    fn insert_bulk<M>(
+        &self,
+        models: &[&M],
+        on_progress: &mut dyn FnMut(BulkInsertProgress),
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        let table = &M::table();
+        let values = models
+            .iter()
+            .map(|m| m.as_column_values())
+            .collect::<Vec<Vec<Option<EzqlValue>>>>();
+        Backend::insert_bulk(self, table, values, on_progress)
+    }
+
+    // ====< Upsert >====
+    fn upsert<M>(
+        &self,
+        models: &[&M],
+        conflict: Vec<String>,
+        on_conflict: OnConflict,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        let table = &M::table();
+        let values = models
+            .iter()
+            .map(|m| m.as_column_values())
+            .collect::<Vec<Vec<Option<EzqlValue>>>>();
+        Backend::upsert(self, table, values, conflict, on_conflict)
+    }
+
+    // ====< Select >====
+    fn select<M>(
+        &self,
+        query: SelectQueryParams,
+    ) -> Result<Vec<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        let table = &M::table();
+        let rows = self.select_model_rows(table, query)?;
+        decode_model_rows(table, rows)
+    }
+
+    // ====< Compound select of model rows >====
+    fn select_compound<M>(
+        &self,
+        compound: CompoundSelect,
+    ) -> Result<Vec<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        // Result columns are named after first select
+        let table = &M::table();
+        let columns = RowColumn::untyped(&compound.column_names());
+        let rows = Backend::select_compound(self, compound)?
+            .into_iter()
+            .map(|values| Row::new(columns.clone(), values))
+            .collect();
+        decode_model_rows(table, rows)
+    }
+
+    // ====< Count >====
+    fn count<M>(
+        &self,
+        query: SelectQueryParams,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        self.count_models(&M::table(), query)
+    }
+
+    // ====< Offset pagination, pages start at 1 >====
+    fn paginate<M>(
+        &self,
+        query: SelectQueryParams,
+        page: usize,
+        per_page: usize,
+    ) -> Result<Page<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        if per_page == 0 {
+            return Err("per_page must be greater than 0".into());
+        }
+        let page = page.max(1);
+        let offset = (page - 1)
+            .checked_mul(per_page)
+            .ok_or_else(|| format!("Page {} with {} per page is out of range", page, per_page))?;
+        let table = &M::table();
+        let total = self.count_models(table, query.clone())?;
+        let rows = self.select_model_rows(
+            table,
+            SelectQueryParams {
+                limit: Some(per_page),
+                offset: Some(offset),
+                ..query
+            },
+        )?;
+        Ok(Page {
+            items: decode_model_rows(table, rows)?,
+            total,
+            page,
+            per_page,
+        })
+    }
+
+    // ====< Keyset pagination >====
+    fn paginate_keyset<M>(
+        &self,
+        query: SelectQueryParams,
+        keyset: Keyset,
+    ) -> Result<KeysetPage<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        let (query, cursor) = keyset.apply(query)?;
+        let table = &M::table();
+        let rows = self.select_model_rows(table, query)?;
+        keyset.page(table, cursor, rows)
+    }
+
+    // ====< Delete >====
+    fn delete<M>(
+        &self,
+        query: SelectQueryParams,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        let table = &M::table();
+        let shape = Shape::select(&query);
+        let delete_query = memoized::<SqliteDialect>(Statement::Delete, table, shape, || {
+            SqliteDialect::delete(table, query)
+        });
+        self.execute(&delete_query)
+    }
+
+    // ====< Delete, returns deleted models >====
+    fn delete_returning<M>(
+        &self,
+        query: SelectQueryParams,
+    ) -> Result<Vec<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        let table = &M::table();
+        let rows = Backend::delete_returning(self, table, query)?;
+        decode_models(table, rows)
+    }
+
+    // ====< Update >====
+    fn update<M>(
+        &self,
+        query: UpdateQueryParams,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        let table = &M::table();
+        Self::check_assignments(table, &query)?;
+        let shape = Shape::update(table, &query);
+        let update_query = memoized::<SqliteDialect>(Statement::Update, table, shape, || {
+            SqliteDialect::update(table, query)
+        });
+        self.execute(&update_query)
+    }
+
+    // ====< Update, returns updated models >====
+    fn update_returning<M>(
+        &self,
+        query: UpdateQueryParams,
+    ) -> Result<Vec<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        let table = &M::table();
+        let rows = Backend::update_returning(self, table, query)?;
+        decode_models(table, rows)
+    }
+
+    // ====< Query raw SQL into models >====
+    fn query_as<M>(
+        &self,
+        sql: &str,
+        params: RawParams,
+    ) -> Result<Vec<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        let rows = Backend::query_raw(self, sql, params)?;
+        decode_model_rows(&M::table(), rows)
+    }
+}
+
+// ====< Impl ToSql for EzqlValue >====
+#[cfg(feature = "sqlite")]
+impl rusqlite::types::ToSql for EzqlValue {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        match self {
+            EzqlValue::Integer(i) => Ok(rusqlite::types::ToSqlOutput::from(*i)),
+            EzqlValue::VarChar(s) | EzqlValue::Enum(s) => {
+                Ok(rusqlite::types::ToSqlOutput::from(s.as_str()))
+            }
+            EzqlValue::Boolean(b) => Ok(rusqlite::types::ToSqlOutput::from(*b)),
+            EzqlValue::Null() => Ok(rusqlite::types::ToSqlOutput::from(rusqlite::types::Null)),
+        }
+    }
+}
+
+// ====< Impl FromSql for EzqlValue >====
+#[cfg(feature = "sqlite")]
+impl rusqlite::types::FromSql for EzqlValue {
+    fn column_result(value: rusqlite::types::ValueRef) -> rusqlite::types::FromSqlResult<Self> {
+        match value {
+            rusqlite::types::ValueRef::Integer(i) => i32::try_from(i)
+                .map(|i| i.to_ezql())
+                .map_err(|_| rusqlite::types::FromSqlError::OutOfRange(i)),
+            rusqlite::types::ValueRef::Text(s) => std::str::from_utf8(s)
+                .map(|s| s.to_ezql())
+                .map_err(|e| rusqlite::types::FromSqlError::Other(Box::new(e))),
+            rusqlite::types::ValueRef::Null => Ok(EzqlValue::Null()),
+            _ => Err(rusqlite::types::FromSqlError::InvalidType),
+        }
+    }
+}
+
+// ========< TESTS >========
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backends,
+        prelude::{decode_column, Column, ColumnProperty, DecodeError, EzqlType},
+        queries::{
+            CompareOp, CompoundSelect, Cte, Cursor, Distinct, Expr, FrameBound, Join, Projection,
+            Subquery, WhereClause, Window,
+        },
+        types::{ConversionError, FromEzql},
+    };
+
+    use super::*;
+    use crate::backends::sqlite_pool::{SqlitePool, SqlitePoolOptions};
+    use crate::components::sql_cache::{memoized_len, SQL_CACHE_CAPACITY};
+    use crate::utils::to_hex;
+    use std::{
+        borrow::Cow,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            OnceLock,
+        },
+    };
+
+    struct User {
+        id: Option<i32>,
+        name: Option<String>,
+        is_active: Option<bool>,
+    }
+
+    impl EzqlModelTrait for User {
+        fn get_table() -> Table {
+            Table {
+                name: "users".to_string(),
+                columns: vec![
+                    Column {
+                        name: "id".to_string(),
+                        data_type: EzqlType::Integer(),
+                        properties: vec![ColumnProperty::PrimaryKey],
+                    },
+                    Column {
+                        name: "name".to_string(),
+                        data_type: EzqlType::VarChar(255),
+                        properties: vec![ColumnProperty::NotNull],
+                    },
+                    Column {
+                        name: "is_active".to_string(),
+                        data_type: EzqlType::Boolean(),
+                        properties: vec![ColumnProperty::default(false)],
+                    },
+                ],
+            }
+        }
+
+        fn as_column_values(&self) -> Vec<Option<EzqlValue>> {
+            vec![
+                self.id.as_ref().map(ToEzql::to_ezql),
+                self.name.as_ref().map(ToEzql::to_ezql),
+                self.is_active.as_ref().map(ToEzql::to_ezql),
+            ]
+        }
+
+        fn from_column_values(
+            values: Vec<Option<EzqlValue>>,
+        ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(Self {
+                id: decode_column(&values, 0)?,
+                name: decode_column(&values, 1)?,
+                is_active: decode_column(&values, 2)?,
+            })
+        }
+    }
+
+    #[test]
     fn test_create_dev_sqlite_backend() {
         let backend = SqliteBackend::new_in_memory();
-        backends::ModelBackend::create_table::<User>(&backend, true).unwrap();
+        backends::ModelBackend::create_table::<User>(&backend, true).unwrap();
+    }
+
+    #[test]
+    fn test_drop_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        backends::ModelBackend::create_table::<User>(&backend, true).unwrap();
+        backends::ModelBackend::drop_table::<User>(&backend, true).unwrap();
+
+        // Insert should fail because table does not exist
+        let user = User {
+            id: None,
+            name: Some("John".to_string()),
+            is_active: Some(true),
+        };
+
+        assert!(backends::ModelBackend::insert::<User>(&backend, &[&user]).is_err());
+    }
+
+    #[test]
+    fn test_insert_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        let user = User {
+            id: None,
+            name: Some("John".to_string()),
+            is_active: Some(true),
+        };
+        backends::ModelBackend::create_table::<User>(&backend, true).unwrap();
+        backends::ModelBackend::insert::<User>(&backend, &[&user]).unwrap();
+    }
+
+    #[test]
+    fn test_select_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        let user = User {
+            id: None,
+            name: Some("John".to_string()),
+            is_active: Some(true),
+        };
+        backends::ModelBackend::create_table::<User>(&backend, true).unwrap();
+        backends::ModelBackend::insert::<User>(&backend, &[&user]).unwrap();
+        let users = backends::ModelBackend::select::<User>(
+            &backend,
+            SelectQueryParams {
+                columns: Some(vec!["name".into()]),
+                where_clause: None,
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                joins: vec![],
+                with: vec![],
+                distinct: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].name, Some("John".to_string()));
+    }
+
+    #[test]
+    fn test_delete_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        let user = User {
+            id: None,
+            name: Some("John".to_string()),
+            is_active: Some(true),
+        };
+        backends::ModelBackend::create_table::<User>(&backend, true).unwrap();
+        backends::ModelBackend::insert::<User>(&backend, &[&user]).unwrap();
+        let users = backends::ModelBackend::select::<User>(
+            &backend,
+            SelectQueryParams {
+                columns: Some(vec!["name".into()]),
+                where_clause: None,
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                joins: vec![],
+                with: vec![],
+                distinct: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].name, Some("John".to_string()));
+
+        backends::ModelBackend::delete::<User>(
+            &backend,
+            SelectQueryParams {
+                columns: None,
+                where_clause: Some(WhereClause::All),
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                joins: vec![],
+                with: vec![],
+                distinct: None,
+            },
+        )
+        .unwrap();
+
+        let users = backends::ModelBackend::select::<User>(
+            &backend,
+            SelectQueryParams {
+                columns: Some(vec!["name".into()]),
+                where_clause: None,
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                joins: vec![],
+                with: vec![],
+                distinct: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(users.len(), 0);
+    }
+
+    #[test]
+    fn test_update_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        let user = User {
+            id: None,
+            name: Some("John".to_string()),
+            is_active: Some(true),
+        };
+        backends::ModelBackend::create_table::<User>(&backend, true).unwrap();
+        backends::ModelBackend::insert::<User>(&backend, &[&user]).unwrap();
+        let users = backends::ModelBackend::select::<User>(
+            &backend,
+            SelectQueryParams {
+                columns: Some(vec!["name".into()]),
+                where_clause: None,
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                joins: vec![],
+                with: vec![],
+                distinct: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].name, Some("John".to_string()));
+
+        backends::ModelBackend::update::<User>(
+            &backend,
+            UpdateQueryParams {
+                set: vec![
+                    ("name".to_string(), "Jane".into()),
+                    ("is_active".to_string(), false.into()),
+                ],
+                where_clause: Some(WhereClause::All),
+            },
+        )
+        .unwrap();
+
+        let users = backends::ModelBackend::select::<User>(
+            &backend,
+            SelectQueryParams {
+                columns: Some(vec!["name".into(), "is_active".into()]),
+                where_clause: None,
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                joins: vec![],
+                with: vec![],
+                distinct: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].name, Some("Jane".to_string()));
+        assert_eq!(users[0].is_active, Some(false));
+    }
+
+    // ====< Custom types >====
+    #[derive(Debug, Clone, PartialEq)]
+    struct Email(String);
+
+    impl ToEzql for Email {
+        fn ezql_type() -> EzqlType {
+            EzqlType::VarChar(255)
+        }
+
+        fn to_ezql(&self) -> EzqlValue {
+            EzqlValue::VarChar(self.0.clone())
+        }
+    }
+
+    impl FromEzql for Email {
+        fn from_ezql(value: EzqlValue) -> Result<Self, ConversionError> {
+            String::from_ezql(value).map(Email)
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Status {
+        Active,
+        Banned,
+    }
+
+    impl ToEzql for Status {
+        fn ezql_type() -> EzqlType {
+            EzqlType::VarChar(16)
+        }
+
+        fn to_ezql(&self) -> EzqlValue {
+            match self {
+                Status::Active => "active".to_ezql(),
+                Status::Banned => "banned".to_ezql(),
+            }
+        }
+    }
+
+    impl FromEzql for Status {
+        fn from_ezql(value: EzqlValue) -> Result<Self, ConversionError> {
+            match &value {
+                EzqlValue::VarChar(s) if s == "active" => Ok(Status::Active),
+                EzqlValue::VarChar(s) if s == "banned" => Ok(Status::Banned),
+                _ => Err(ConversionError::new("Status", value)),
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Permissions(i32);
+
+    impl Permissions {
+        const READ: Permissions = Permissions(0b01);
+        const WRITE: Permissions = Permissions(0b10);
+    }
+
+    impl ToEzql for Permissions {
+        fn ezql_type() -> EzqlType {
+            EzqlType::Integer()
+        }
+
+        fn to_ezql(&self) -> EzqlValue {
+            EzqlValue::Integer(self.0)
+        }
+    }
+
+    impl FromEzql for Permissions {
+        fn from_ezql(value: EzqlValue) -> Result<Self, ConversionError> {
+            i32::from_ezql(value).map(Permissions)
+        }
+    }
+
+    struct Account {
+        id: Option<i32>,
+        email: Option<Email>,
+        status: Option<Status>,
+        permissions: Option<Permissions>,
+    }
+
+    impl EzqlModelTrait for Account {
+        fn get_table() -> Table {
+            Table {
+                name: "accounts".to_string(),
+                columns: vec![
+                    Column {
+                        name: "id".to_string(),
+                        data_type: i32::ezql_type(),
+                        properties: vec![ColumnProperty::PrimaryKey],
+                    },
+                    Column {
+                        name: "email".to_string(),
+                        data_type: Email::ezql_type(),
+                        properties: vec![ColumnProperty::NotNull],
+                    },
+                    Column {
+                        name: "status".to_string(),
+                        data_type: Status::ezql_type(),
+                        properties: vec![ColumnProperty::default(Status::Active)],
+                    },
+                    Column {
+                        name: "permissions".to_string(),
+                        data_type: Permissions::ezql_type(),
+                        properties: vec![ColumnProperty::default(Permissions::READ)],
+                    },
+                ],
+            }
+        }
+
+        fn as_column_values(&self) -> Vec<Option<EzqlValue>> {
+            vec![
+                self.id.as_ref().map(ToEzql::to_ezql),
+                self.email.as_ref().map(ToEzql::to_ezql),
+                self.status.as_ref().map(ToEzql::to_ezql),
+                self.permissions.as_ref().map(ToEzql::to_ezql),
+            ]
+        }
+
+        fn from_column_values(
+            values: Vec<Option<EzqlValue>>,
+        ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(Self {
+                id: decode_column(&values, 0)?,
+                email: decode_column(&values, 1)?,
+                status: decode_column(&values, 2)?,
+                permissions: decode_column(&values, 3)?,
+            })
+        }
+    }
+
+    #[test]
+    fn test_custom_types_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        let account = Account {
+            id: None,
+            email: Some(Email("john@example.com".to_string())),
+            status: Some(Status::Banned),
+            permissions: Some(Permissions(Permissions::READ.0 | Permissions::WRITE.0)),
+        };
+        backends::ModelBackend::create_table::<Account>(&backend, true).unwrap();
+        backends::ModelBackend::insert::<Account>(&backend, &[&account]).unwrap();
+        let accounts = backends::ModelBackend::select::<Account>(
+            &backend,
+            SelectQueryParams {
+                columns: None,
+                where_clause: Some(WhereClause::Eq(
+                    "status".to_string(),
+                    Status::Banned.to_ezql(),
+                )),
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                joins: vec![],
+                with: vec![],
+                distinct: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(
+            accounts[0].email,
+            Some(Email("john@example.com".to_string()))
+        );
+        assert_eq!(accounts[0].status, Some(Status::Banned));
+        assert_eq!(accounts[0].permissions, Some(Permissions(0b11)));
+    }
+
+    #[test]
+    fn test_custom_type_conversion_error() {
+        let error = Status::from_ezql(EzqlValue::VarChar("deleted".to_string())).unwrap_err();
+        assert_eq!(error.expected, "Status");
+        assert_eq!(error.actual, EzqlValue::VarChar("deleted".to_string()));
+        assert!(i32::from_ezql(EzqlValue::Boolean(true)).is_err());
+    }
+
+    #[test]
+    fn test_select_decode_error_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        let user = User {
+            id: None,
+            name: Some("John".to_string()),
+            is_active: Some(true),
+        };
+        backends::ModelBackend::create_table::<User>(&backend, true).unwrap();
+        backends::ModelBackend::insert::<User>(&backend, &[&user, &user]).unwrap();
+
+        // SQLite happily stores text in a BOOLEAN column
+        backends::ModelBackend::update::<User>(
+            &backend,
+            UpdateQueryParams {
+                set: vec![("is_active".to_string(), "yes".into())],
+                where_clause: Some(WhereClause::Eq("id".to_string(), 2.into())),
+            },
+        )
+        .unwrap();
+
+        let error = backends::ModelBackend::select::<User>(
+            &backend,
+            SelectQueryParams {
+                columns: None,
+                where_clause: None,
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                joins: vec![],
+                with: vec![],
+                distinct: None,
+            },
+        )
+        .err()
+        .unwrap();
+        let error = error.downcast_ref::<DecodeError>().unwrap();
+        assert_eq!(error.row, 1);
+        assert_eq!(error.column, Some("is_active".to_string()));
+
+        let source = error.source.downcast_ref::<ConversionError>().unwrap();
+        assert_eq!(source.expected, "bool");
+        assert_eq!(source.actual_type(), "VarChar");
+    }
+
+    #[test]
+    fn test_try_from_ezql_value() {
+        assert_eq!(i32::try_from(EzqlValue::Integer(7)), Ok(7));
+        assert_eq!(bool::try_from(EzqlValue::Integer(0)), Ok(false));
+        assert_eq!(
+            String::try_from(EzqlValue::VarChar("John".to_string())),
+            Ok("John".to_string())
+        );
+
+        let error = i32::try_from(EzqlValue::VarChar("John".to_string())).unwrap_err();
+        assert_eq!(error.expected, "i32");
+        assert_eq!(error.actual_type(), "VarChar");
+        assert_eq!(error.column, None);
+    }
+
+    // ====< Nullable columns >====
+    #[derive(Debug, PartialEq)]
+    struct Profile {
+        id: Option<i32>,
+        nickname: String,
+        bio: Option<String>,
+    }
+
+    impl EzqlModelTrait for Profile {
+        fn get_table() -> Table {
+            Table {
+                name: "profiles".to_string(),
+                columns: vec![
+                    Column::new::<Option<i32>>("id", vec![ColumnProperty::PrimaryKey]),
+                    Column::new::<String>("nickname", vec![]),
+                    Column::new::<Option<String>>("bio", vec![]),
+                ],
+            }
+        }
+
+        fn as_column_values(&self) -> Vec<Option<EzqlValue>> {
+            vec![
+                self.id.as_ref().map(ToEzql::to_ezql),
+                Some(self.nickname.to_ezql()),
+                Some(self.bio.to_ezql()),
+            ]
+        }
+
+        fn from_column_values(
+            values: Vec<Option<EzqlValue>>,
+        ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(Self {
+                id: decode_column(&values, 0)?,
+                nickname: decode_column(&values, 1)?,
+                bio: decode_column(&values, 2)?,
+            })
+        }
+    }
+
+    #[test]
+    fn test_nullable_columns_sqlite_backend() {
+        let table = Profile::get_table();
+        assert!(!table.columns[0].is_nullable());
+        assert!(!table.columns[1].is_nullable());
+        assert!(table.columns[2].is_nullable());
+
+        let backend = SqliteBackend::new_in_memory();
+        let with_bio = Profile {
+            id: None,
+            nickname: "john".to_string(),
+            bio: Some("Hello".to_string()),
+        };
+        let without_bio = Profile {
+            id: None,
+            nickname: "jane".to_string(),
+            bio: None,
+        };
+        backends::ModelBackend::create_table::<Profile>(&backend, true).unwrap();
+        backends::ModelBackend::insert::<Profile>(&backend, &[&with_bio]).unwrap();
+        backends::ModelBackend::insert::<Profile>(&backend, &[&without_bio]).unwrap();
+
+        // NOT NULL is enforced for non-optional fields
+        assert!(Backend::insert(
+            &backend,
+            &table,
+            vec![vec![None, Some(EzqlValue::Null()), None]]
+        )
+        .is_err());
+
+        let profiles = backends::ModelBackend::select::<Profile>(
+            &backend,
+            SelectQueryParams {
+                columns: None,
+                where_clause: Some(WhereClause::Eq("bio".to_string(), EzqlValue::Null())),
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                joins: vec![],
+                with: vec![],
+                distinct: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].nickname, "jane");
+        assert_eq!(profiles[0].bio, None);
+
+        let profiles = backends::ModelBackend::select::<Profile>(
+            &backend,
+            SelectQueryParams {
+                columns: None,
+                where_clause: Some(WhereClause::Ne("bio".to_string(), None::<String>.into())),
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                joins: vec![],
+                with: vec![],
+                distinct: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].bio, Some("Hello".to_string()));
+
+        // Non-optional fields can not be decoded from NULL
+        assert!(backends::ModelBackend::select::<Profile>(
+            &backend,
+            SelectQueryParams {
+                columns: Some(vec!["bio".into()]),
+                where_clause: None,
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                joins: vec![],
+                with: vec![],
+                distinct: None,
+            },
+        )
+        .is_err());
+    }
+
+    // ====< Enum columns >====
+    #[derive(Debug, Clone, PartialEq)]
+    enum Role {
+        Admin,
+        Member,
+    }
+
+    crate::impl_ezql_enum!(Role {
+        Admin => "admin",
+        Member => "member",
+    });
+
+    struct Membership {
+        id: Option<i32>,
+        role: Role,
+    }
+
+    impl EzqlModelTrait for Membership {
+        fn get_table() -> Table {
+            Table {
+                name: "memberships".to_string(),
+                columns: vec![
+                    Column::new::<Option<i32>>("id", vec![ColumnProperty::PrimaryKey]),
+                    Column::new::<Role>("role", vec![ColumnProperty::default(Role::Member)]),
+                ],
+            }
+        }
+
+        fn as_column_values(&self) -> Vec<Option<EzqlValue>> {
+            vec![
+                self.id.as_ref().map(ToEzql::to_ezql),
+                Some(self.role.to_ezql()),
+            ]
+        }
+
+        fn from_column_values(
+            values: Vec<Option<EzqlValue>>,
+        ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(Self {
+                id: decode_column(&values, 0)?,
+                role: decode_column(&values, 1)?,
+            })
+        }
+    }
+
+    #[test]
+    fn test_enum_columns_sqlite_backend() {
+        let query = SqliteDialect::create_table(false, Membership::get_table());
+        assert_eq!(
+            query.sql,
+            "CREATE TABLE memberships (id INTEGER PRIMARY KEY, \
+             role TEXT DEFAULT 'member' NOT NULL CHECK(role IN ('admin', 'member')));"
+        );
+
+        let backend = SqliteBackend::new_in_memory();
+        let membership = Membership {
+            id: None,
+            role: Role::Admin,
+        };
+        backends::ModelBackend::create_table::<Membership>(&backend, true).unwrap();
+        backends::ModelBackend::insert::<Membership>(&backend, &[&membership]).unwrap();
+
+        // Values outside of the enum are rejected by the database
+        assert!(Backend::insert(
+            &backend,
+            &Membership::get_table(),
+            vec![vec![None, Some("owner".into())]]
+        )
+        .is_err());
+
+        let memberships = backends::ModelBackend::select::<Membership>(
+            &backend,
+            SelectQueryParams {
+                columns: None,
+                where_clause: Some(WhereClause::Eq("role".to_string(), Role::Admin.into())),
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                joins: vec![],
+                with: vec![],
+                distinct: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(memberships.len(), 1);
+        assert_eq!(memberships[0].role, Role::Admin);
+        assert!(Role::from_ezql("owner".into()).is_err());
+    }
+
+    // ====< Check constraints and generated columns >====
+    fn product_table() -> Table {
+        Table {
+            name: "products".to_string(),
+            columns: vec![
+                Column::new::<Option<i32>>("id", vec![ColumnProperty::PrimaryKey]),
+                Column::new::<String>("email", vec![ColumnProperty::Unique]),
+                Column::new::<i32>("price", vec![ColumnProperty::check("price >= 0")]),
+                Column::new::<Option<String>>(
+                    "email_lower",
+                    vec![ColumnProperty::generated("lower(email)", true)],
+                ),
+                Column::new::<Option<i32>>(
+                    "price_with_tax",
+                    vec![ColumnProperty::generated("price * 2", false)],
+                ),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_check_and_generated_columns_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        let table = product_table();
+        Backend::create_table(&backend, false, table.clone()).unwrap();
+
+        // Generated columns are skipped on insert
+        Backend::insert(
+            &backend,
+            &table,
+            vec![vec![
+                None,
+                Some("John@Example.com".into()),
+                Some(10.into()),
+                Some("ignored".into()),
+                Some(0.into()),
+            ]],
+        )
+        .unwrap();
+
+        // Check constraint is enforced
+        assert!(Backend::insert(
+            &backend,
+            &table,
+            vec![vec![
+                None,
+                Some("jane@example.com".into()),
+                Some((-1).into())
+            ]],
+        )
+        .is_err());
+
+        // Generated columns are skipped on update
+        Backend::update(
+            &backend,
+            &table,
+            UpdateQueryParams {
+                set: vec![
+                    ("price".to_string(), 20.into()),
+                    ("email_lower".to_string(), "ignored".into()),
+                ],
+                where_clause: None,
+            },
+        )
+        .unwrap();
+
+        let rows = Backend::select(
+            &backend,
+            &table,
+            SelectQueryParams {
+                columns: None,
+                where_clause: None,
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                joins: vec![],
+                with: vec![],
+                distinct: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(rows[0][3], Some("john@example.com".into()));
+        assert_eq!(rows[0][4], Some(40.into()));
+
+        // Update of generated columns only has nothing to set
+        let error = Backend::update(
+            &backend,
+            &table,
+            UpdateQueryParams {
+                set: vec![("email_lower".to_string(), "ignored".into())],
+                where_clause: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "No writable columns to update in table products"
+        );
+    }
+
+    #[test]
+    fn test_introspect_table_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        let table = product_table();
+        Backend::create_table(&backend, false, table.clone()).unwrap();
+        backends::ModelBackend::create_table::<Membership>(&backend, false).unwrap();
+
+        let introspected = Backend::introspect_table(&backend, "products").unwrap();
+        assert_eq!(introspected.columns.len(), table.columns.len());
+        for (column, expected) in introspected.columns.iter().zip(table.columns.iter()) {
+            assert_eq!(column.name, expected.name);
+            assert_eq!(column.data_type, expected.data_type);
+            assert_eq!(column.properties.len(), expected.properties.len());
+            for property in expected.properties.iter() {
+                assert!(column.properties.contains(property));
+            }
+        }
+
+        let introspected = Backend::introspect_table(&backend, "memberships").unwrap();
+        assert_eq!(
+            introspected.columns[1].data_type,
+            EzqlType::Enum {
+                name: "role".to_string(),
+                variants: vec!["admin".to_string(), "member".to_string()],
+            }
+        );
+        assert_eq!(
+            introspected.columns[1].properties,
+            vec![
+                ColumnProperty::NotNull,
+                ColumnProperty::Default(EzqlValue::Enum("member".to_string())),
+            ]
+        );
+
+        // Affinity types and UNIQUE inside expressions
+        Backend::execute_raw(
+            &backend,
+            "CREATE TABLE notes (\
+                id INTEGER PRIMARY KEY, \
+                body TEXT NOT NULL CHECK (body <> 'UNIQUE'), \
+                slug CHARACTER(20) UNIQUE, \
+                tag NVARCHAR(10) DEFAULT 'not UNIQUE')",
+            RawParams::none(),
+        )
+        .unwrap();
+        let introspected = Backend::introspect_table(&backend, "notes").unwrap();
+        assert_eq!(
+            introspected
+                .columns
+                .iter()
+                .map(|c| (
+                    c.data_type.clone(),
+                    c.properties.contains(&ColumnProperty::Unique)
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (EzqlType::Integer(), false),
+                (EzqlType::VarChar(0), false),
+                (EzqlType::VarChar(20), true),
+                (EzqlType::VarChar(10), false),
+            ]
+        );
+
+        Backend::execute_raw(
+            &backend,
+            "CREATE TABLE prices (amount REAL)",
+            RawParams::none(),
+        )
+        .unwrap();
+        assert_eq!(
+            Backend::introspect_table(&backend, "prices")
+                .unwrap_err()
+                .to_string(),
+            "Unsupported type REAL of column amount"
+        );
+
+        assert!(Backend::introspect_table(&backend, "missing").is_err());
+    }
+
+    // ====< Upsert >====
+    #[derive(Debug, Clone)]
+    struct Setting {
+        id: Option<i32>,
+        key: String,
+        value: String,
+        version: i32,
+    }
+
+    impl EzqlModelTrait for Setting {
+        fn get_table() -> Table {
+            Table {
+                name: "settings".to_string(),
+                columns: vec![
+                    Column::new::<Option<i32>>("id", vec![ColumnProperty::PrimaryKey]),
+                    Column::new::<String>("key", vec![ColumnProperty::Unique]),
+                    Column::new::<String>("value", vec![]),
+                    Column::new::<i32>("version", vec![]),
+                ],
+            }
+        }
+
+        fn table() -> Cow<'static, Table> {
+            static TABLE: OnceLock<Table> = OnceLock::new();
+            Cow::Borrowed(TABLE.get_or_init(Self::get_table))
+        }
+
+        fn as_column_values(&self) -> Vec<Option<EzqlValue>> {
+            vec![
+                self.id.as_ref().map(ToEzql::to_ezql),
+                Some(self.key.to_ezql()),
+                Some(self.value.to_ezql()),
+                Some(self.version.to_ezql()),
+            ]
+        }
+
+        fn from_column_values(
+            values: Vec<Option<EzqlValue>>,
+        ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(Self {
+                id: decode_column(&values, 0)?,
+                key: decode_column(&values, 1)?,
+                value: decode_column(&values, 2)?,
+                version: decode_column(&values, 3)?,
+            })
+        }
+    }
+
+    fn setting(key: &str, value: &str, version: i32) -> Setting {
+        Setting {
+            id: None,
+            key: key.to_string(),
+            value: value.to_string(),
+            version,
+        }
+    }
+
+    fn select_setting(backend: &SqliteBackend, key: &str) -> Setting {
+        backends::ModelBackend::select::<Setting>(
+            backend,
+            SelectQueryParams {
+                columns: None,
+                where_clause: Some(WhereClause::Eq("key".to_string(), key.into())),
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                joins: vec![],
+                with: vec![],
+                distinct: None,
+            },
+        )
+        .unwrap()
+        .remove(0)
+    }
+
+    #[test]
+    fn test_upsert_sqlite_backend() {
+        let query = SqliteDialect::upsert(
+            &Setting::get_table(),
+            vec![setting("theme", "dark", 1).as_column_values()],
+            vec!["key".to_string()],
+            OnConflict::UpdateAll,
+        );
+        assert_eq!(
+            query.sql,
+            "INSERT INTO settings (key, value, version) VALUES (?, ?, ?) \
+             ON CONFLICT (key) DO UPDATE SET value = excluded.value, version = excluded.version;"
+        );
+
+        let backend = SqliteBackend::new_in_memory();
+        let conflict = vec!["key".to_string()];
+        backends::ModelBackend::create_table::<Setting>(&backend, true).unwrap();
+        backends::ModelBackend::insert::<Setting>(&backend, &[&setting("theme", "dark", 1)])
+            .unwrap();
+
+        // Do nothing keeps existing row
+        backends::ModelBackend::upsert::<Setting>(
+            &backend,
+            &[&setting("theme", "light", 2), &setting("lang", "en", 1)],
+            conflict.clone(),
+            OnConflict::DoNothing,
+        )
+        .unwrap();
+        assert_eq!(select_setting(&backend, "theme").value, "dark");
+        assert_eq!(select_setting(&backend, "lang").value, "en");
+
+        // Overwrite all columns
+        backends::ModelBackend::upsert::<Setting>(
+            &backend,
+            &[&setting("theme", "light", 2)],
+            conflict.clone(),
+            OnConflict::UpdateAll,
+        )
+        .unwrap();
+        let theme = select_setting(&backend, "theme");
+        assert_eq!((theme.value.as_str(), theme.version), ("light", 2));
+
+        // Overwrite listed columns only
+        backends::ModelBackend::upsert::<Setting>(
+            &backend,
+            &[&setting("theme", "blue", 3)],
+            conflict.clone(),
+            OnConflict::Update(vec!["version".to_string()]),
+        )
+        .unwrap();
+        let theme = select_setting(&backend, "theme");
+        assert_eq!((theme.value.as_str(), theme.version), ("light", 3));
+
+        // Custom set with where clause
+        backends::ModelBackend::upsert::<Setting>(
+            &backend,
+            &[&setting("theme", "red", 4), &setting("lang", "pl", 4)],
+            conflict,
+            OnConflict::Set(UpdateQueryParams {
+                set: vec![("value".to_string(), "custom".into())],
+                where_clause: Some(WhereClause::Eq("key".to_string(), "lang".into())),
+            }),
+        )
+        .unwrap();
+        assert_eq!(select_setting(&backend, "theme").value, "light");
+        assert_eq!(select_setting(&backend, "lang").value, "custom");
+
+        let settings = backends::ModelBackend::select::<Setting>(
+            &backend,
+            SelectQueryParams {
+                columns: None,
+                where_clause: None,
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                joins: vec![],
+                with: vec![],
+                distinct: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(settings.len(), 2);
+
+        // Without conflict columns any constraint conflict is ignored
+        let query = SqliteDialect::upsert(
+            &Setting::get_table(),
+            vec![setting("theme", "blue", 3).as_column_values()],
+            vec![],
+            OnConflict::DoNothing,
+        );
+        assert_eq!(
+            query.sql,
+            "INSERT INTO settings (key, value, version) VALUES (?, ?, ?) ON CONFLICT DO NOTHING;"
+        );
+        let inserted = backends::ModelBackend::upsert::<Setting>(
+            &backend,
+            &[&setting("theme", "blue", 3)],
+            vec![],
+            OnConflict::DoNothing,
+        )
+        .unwrap();
+        assert_eq!(inserted, 0);
+        assert!(backends::ModelBackend::upsert::<Setting>(
+            &backend,
+            &[&setting("theme", "blue", 3)],
+            vec![],
+            OnConflict::UpdateAll,
+        )
+        .is_err());
+        assert_eq!(select_setting(&backend, "theme").value, "light");
+    }
+
+    // ====< Insert returning >====
+    #[test]
+    fn test_insert_returns_keys_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        let user = User {
+            id: None,
+            name: Some("John".to_string()),
+            is_active: None,
+        };
+        backends::ModelBackend::create_table::<User>(&backend, true).unwrap();
+        let keys = backends::ModelBackend::insert::<User>(&backend, &[&user, &user]).unwrap();
+        assert_eq!(keys, vec![EzqlValue::Integer(1), EzqlValue::Integer(2)]);
+
+        // Fallback for SQLite without RETURNING
+        let keys = backend
+            .insert_rowids(&User::get_table(), vec![user.as_column_values()])
+            .unwrap();
+        assert_eq!(keys, vec![EzqlValue::Integer(3)]);
+
+        // Nothing to insert
+        assert!(backends::ModelBackend::insert::<User>(&backend, &[])
+            .unwrap()
+            .is_empty());
+        assert!(
+            backends::ModelBackend::insert_returning::<User>(&backend, &[])
+                .unwrap()
+                .is_empty()
+        );
+
+        // Rowids above i32::MAX are not truncated
+        let last = User {
+            id: Some(i32::MAX),
+            name: Some("Jane".to_string()),
+            is_active: None,
+        };
+        backends::ModelBackend::insert::<User>(&backend, &[&last]).unwrap();
+        assert!(backends::ModelBackend::insert::<User>(&backend, &[&user]).is_err());
+        assert!(backend
+            .insert_rowids(&User::get_table(), vec![user.as_column_values()])
+            .unwrap_err()
+            .to_string()
+            .ends_with("does not fit into Integer"));
+    }
+
+    #[test]
+    fn test_insert_returning_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        let user = User {
+            id: None,
+            name: Some("John".to_string()),
+            is_active: None,
+        };
+        backends::ModelBackend::create_table::<User>(&backend, true).unwrap();
+        let users =
+            backends::ModelBackend::insert_returning::<User>(&backend, &[&user, &user]).unwrap();
+        assert_eq!(users.len(), 2);
+        assert_eq!(users[1].id, Some(2));
+        assert_eq!(users[1].name, Some("John".to_string()));
+        // Default filled in by the database
+        assert_eq!(users[1].is_active, Some(false));
+
+        // Fallback for SQLite without RETURNING
+        let rows = backend
+            .insert_returning_fallback(&User::get_table(), vec![user.as_column_values()])
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![vec![
+                Some(EzqlValue::Integer(3)),
+                Some("John".into()),
+                Some(EzqlValue::Integer(0)),
+            ]]
+        );
+    }
+
+    // ====< Update and delete returning >====
+    fn insert_settings(backend: &SqliteBackend) {
+        backends::ModelBackend::create_table::<Setting>(backend, true).unwrap();
+        backends::ModelBackend::insert::<Setting>(
+            backend,
+            &[
+                &setting("theme", "dark", 1),
+                &setting("lang", "en", 1),
+                &setting("tz", "utc", 2),
+            ],
+        )
+        .unwrap();
+    }
+
+    fn bump_versions() -> UpdateQueryParams {
+        UpdateQueryParams {
+            set: vec![("version".to_string(), 5.into())],
+            where_clause: Some(WhereClause::Eq("version".to_string(), 1.into())),
+        }
+    }
+
+    fn version_two() -> SelectQueryParams {
+        SelectQueryParams {
+            columns: None,
+            where_clause: Some(WhereClause::Eq("version".to_string(), 2.into())),
+            order_by: vec![],
+            limit: None,
+            offset: None,
+            joins: vec![],
+            with: vec![],
+            distinct: None,
+        }
+    }
+
+    #[test]
+    fn test_update_returning_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_settings(&backend);
+
+        let updated =
+            backends::ModelBackend::update_returning::<Setting>(&backend, bump_versions()).unwrap();
+        assert_eq!(updated.len(), 2);
+        assert_eq!(updated[0].key, "theme");
+        assert_eq!(updated[1].key, "lang");
+        assert!(updated.iter().all(|s| s.version == 5));
+
+        // Fallback for SQLite without RETURNING
+        let backend = SqliteBackend::new_in_memory();
+        insert_settings(&backend);
+        let rows = backend
+            .update_returning_fallback(&Setting::get_table(), bump_versions())
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|row| row[3] == Some(5.into())));
+    }
+
+    #[test]
+    fn test_delete_returning_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_settings(&backend);
+
+        let deleted =
+            backends::ModelBackend::delete_returning::<Setting>(&backend, version_two()).unwrap();
+        assert_eq!(deleted.len(), 1);
+        assert_eq!(deleted[0].key, "tz");
+        assert_eq!(
+            backends::ModelBackend::delete::<Setting>(&backend, version_two()).unwrap(),
+            0
+        );
+
+        // Fallback for SQLite without RETURNING
+        let backend = SqliteBackend::new_in_memory();
+        insert_settings(&backend);
+        let rows = backend
+            .delete_returning_fallback(&Setting::get_table(), version_two())
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][1], Some("tz".into()));
+        assert_eq!(
+            backends::ModelBackend::delete::<Setting>(&backend, version_two()).unwrap(),
+            0
+        );
+
+        // Only the returned rows are deleted when limited
+        let first_version_one = || SelectQueryParams {
+            where_clause: Some(WhereClause::Eq("version".to_string(), 1.into())),
+            order_by: vec![OrderBy::desc("key")],
+            limit: Some(1),
+            ..SelectQueryParams::default()
+        };
+        let deleted =
+            backends::ModelBackend::delete_returning::<Setting>(&backend, first_version_one())
+                .unwrap();
+        assert_eq!(deleted.len(), 1);
+        assert_eq!(deleted[0].key, "theme");
+        let rows = backend
+            .delete_returning_fallback(&Setting::get_table(), first_version_one())
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][1], Some("lang".into()));
+        assert_eq!(
+            backends::ModelBackend::count::<Setting>(&backend, SelectQueryParams::default())
+                .unwrap(),
+            0
+        );
+    }
+
+    // ====< Bulk insert >====
+    #[test]
+    fn test_multi_row_insert_query() {
+        let query = SqliteDialect::insert(
+            &User::get_table(),
+            vec![
+                vec![None, Some("John".into()), None],
+                vec![Some(7.into()), Some("Jane".into()), Some(true.into())],
+            ],
+        );
+        assert_eq!(
+            query.sql,
+            "INSERT INTO users (id, name, is_active) VALUES (?, ?, ?), (?, ?, ?);"
+        );
+        assert_eq!(
+            query.params,
+            vec![
+                EzqlValue::Null(),
+                "John".into(),
+                false.into(),
+                7.into(),
+                "Jane".into(),
+                true.into(),
+            ]
+        );
+
+        // Primary key is left out when no row specifies it
+        let query = SqliteDialect::insert(
+            &User::get_table(),
+            vec![vec![None, Some("John".into()), None]],
+        );
+        assert_eq!(
+            query.sql,
+            "INSERT INTO users (name, is_active) VALUES (?, ?);"
+        );
+    }
+
+    #[test]
+    fn test_insert_bulk_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        backends::ModelBackend::create_table::<User>(&backend, true).unwrap();
+
+        let users = (0..2500)
+            .map(|i| User {
+                id: None,
+                name: Some(format!("User {}", i)),
+                is_active: Some(i % 2 == 0),
+            })
+            .collect::<Vec<User>>();
+        let mut progress = Vec::new();
+        let inserted = backends::ModelBackend::insert_bulk::<User>(
+            &backend,
+            &users.iter().collect::<Vec<&User>>(),
+            &mut |p| progress.push(p),
+        )
+        .unwrap();
+        assert_eq!(inserted, 2500);
+
+        // 999 parameters allow 333 rows of 3 columns per chunk
+        assert_eq!(progress.len(), 8);
+        assert_eq!(progress[0].rows_inserted, 333);
+        assert_eq!(
+            progress[7],
+            BulkInsertProgress {
+                chunk: 8,
+                chunks: 8,
+                rows_inserted: 2500,
+                total_rows: 2500,
+            }
+        );
+
+        let users = backends::ModelBackend::select::<User>(
+            &backend,
+            SelectQueryParams {
+                columns: None,
+                where_clause: Some(WhereClause::Eq("id".to_string(), 2500.into())),
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                joins: vec![],
+                with: vec![],
+                distinct: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(users[0].name, Some("User 2499".to_string()));
+    }
+
+    #[test]
+    fn test_insert_bulk_rollback_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        let table = User::get_table();
+        backends::ModelBackend::create_table::<User>(&backend, true).unwrap();
+
+        // Last row violates NOT NULL constraint
+        let mut rows = vec![vec![None, Some("John".into()), None]; 1000];
+        rows.push(vec![None, None, None]);
+        assert!(Backend::insert_bulk(&backend, &table, rows, &mut |_| {}).is_err());
+
+        let users = Backend::select(
+            &backend,
+            &table,
+            SelectQueryParams {
+                columns: None,
+                where_clause: None,
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                joins: vec![],
+                with: vec![],
+                distinct: None,
+            },
+        )
+        .unwrap();
+        assert!(users.is_empty());
+
+        // Failed insert inside open transaction keeps earlier writes
+        Backend::execute_raw(&backend, "BEGIN;", RawParams::none()).unwrap();
+        Backend::insert(
+            &backend,
+            &table,
+            vec![vec![None, Some("Jane".into()), None]],
+        )
+        .unwrap();
+        let rows = vec![
+            vec![None, Some("John".into()), None],
+            vec![None, None, None],
+        ];
+        assert!(Backend::insert_bulk(&backend, &table, rows, &mut |_| {}).is_err());
+        let rows = vec![vec![None, Some("John".into()), None]; 2];
+        assert_eq!(
+            Backend::insert_bulk(&backend, &table, rows, &mut |_| {}).unwrap(),
+            2
+        );
+        Backend::execute_raw(&backend, "COMMIT;", RawParams::none()).unwrap();
+        assert_eq!(
+            Backend::count(&backend, &table, SelectQueryParams::default()).unwrap(),
+            3
+        );
+    }
+
+    // ====< Streaming select >====
+    fn insert_users(backend: &SqliteBackend, count: usize) {
+        backends::ModelBackend::create_table::<User>(backend, true).unwrap();
+        let rows = (0..count)
+            .map(|i| vec![None, Some(format!("User {}", i).into()), None])
+            .collect();
+        Backend::insert_bulk(backend, &User::get_table(), rows, &mut |_| {}).unwrap();
+    }
+
+    #[test]
+    fn test_select_iter_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_users(&backend, 10_000);
+
+        let mut stmt = backend
+            .select_iter::<User>(SelectQueryParams {
+                columns: None,
+                where_clause: Some(WhereClause::Gt("id".to_string(), 100.into())),
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                joins: vec![],
+                with: vec![],
+                distinct: None,
+            })
+            .unwrap();
+        let mut count = 0;
+        for user in stmt.iter().unwrap() {
+            let user = user.unwrap();
+            assert_eq!(user.name, Some(format!("User {}", user.id.unwrap() - 1)));
+            count += 1;
+        }
+        assert_eq!(count, 9_900);
+
+        // Statement can be iterated again
+        assert_eq!(stmt.iter().unwrap().take(5).count(), 5);
+    }
+
+    #[test]
+    fn test_select_batches_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_users(&backend, 2_500);
+
+        let mut stmt = backend
+            .select_iter::<User>(SelectQueryParams {
+                columns: Some(vec!["name".into()]),
+                where_clause: None,
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                joins: vec![],
+                with: vec![],
+                distinct: None,
+            })
+            .unwrap();
+        let sizes = stmt
+            .batches(1_000)
+            .unwrap()
+            .map(|batch| batch.unwrap().len())
+            .collect::<Vec<usize>>();
+        assert_eq!(sizes, vec![1_000, 1_000, 500]);
+
+        // Huge batch size does not allocate up front
+        let mut stmt = backend
+            .select_iter::<User>(SelectQueryParams::default())
+            .unwrap();
+        let sizes = stmt
+            .batches(usize::MAX)
+            .unwrap()
+            .map(|batch| batch.unwrap().len())
+            .collect::<Vec<usize>>();
+        assert_eq!(sizes, vec![2_500]);
+    }
+
+    #[test]
+    fn test_select_iter_decode_error_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_users(&backend, 3);
+        backends::ModelBackend::update::<User>(
+            &backend,
+            UpdateQueryParams {
+                set: vec![("is_active".to_string(), "yes".into())],
+                where_clause: Some(WhereClause::Eq("id".to_string(), 2.into())),
+            },
+        )
+        .unwrap();
+
+        let mut stmt = backend
+            .select_iter::<User>(SelectQueryParams {
+                columns: None,
+                where_clause: None,
+                order_by: vec![],
+                limit: None,
+                offset: None,
+                joins: vec![],
+                with: vec![],
+                distinct: None,
+            })
+            .unwrap();
+        let results = stmt.iter().unwrap().collect::<Vec<_>>();
+        assert!(results[0].is_ok());
+        let error = results[1].as_ref().err().unwrap();
+        assert_eq!(error.downcast_ref::<DecodeError>().unwrap().row, 1);
+        assert!(results[2].is_ok());
+    }
+
+    // ====< Pagination >====
+    #[test]
+    fn test_cursor_encoding() {
+        let cursor = Cursor::After(vec![
+            7.into(),
+            "a.b:c".into(),
+            true.into(),
+            EzqlValue::Enum("admin".to_string()),
+            EzqlValue::Null(),
+        ]);
+        assert_eq!(Cursor::decode(&cursor.encode()).unwrap(), cursor);
+        assert!(Cursor::decode("not a cursor").is_err());
+        // Client tokens must not panic on malformed values
+        for bad in ["a:é", "a:éx", "a:i", "a:s0", "a", ":i1"] {
+            assert!(Cursor::decode(&to_hex(bad.as_bytes())).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_offset_pagination_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_users(&backend, 25);
+
+        let page = backends::ModelBackend::paginate::<User>(
+            &backend,
+            SelectQueryParams {
+                order_by: vec![OrderBy::desc("id")],
+                ..Default::default()
+            },
+            2,
+            10,
+        )
+        .unwrap();
+        assert_eq!(page.total, 25);
+        assert_eq!(page.total_pages(), 3);
+        assert_eq!(page.items.len(), 10);
+        assert_eq!(page.items[0].id, Some(15));
+
+        // Out of range page sizes are rejected instead of overflowing
+        for (page, per_page) in [(1, 0), (usize::MAX, 2)] {
+            let result = backends::ModelBackend::paginate::<User>(
+                &backend,
+                SelectQueryParams::default(),
+                page,
+                per_page,
+            );
+            assert!(result.is_err());
+        }
+        for per_page in [0, usize::MAX] {
+            let keyset = Keyset {
+                order_by: vec![OrderBy::asc("id")],
+                cursor: None,
+                per_page,
+            };
+            let result = backends::ModelBackend::paginate_keyset::<User>(
+                &backend,
+                SelectQueryParams::default(),
+                keyset,
+            );
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn test_keyset_pagination_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_users(&backend, 25);
+        let query = SelectQueryParams {
+            columns: Some(vec!["name".into()]),
+            where_clause: Some(WhereClause::Gt("id".to_string(), 2.into())),
+            ..Default::default()
+        };
+        let keyset = |cursor: Option<String>| Keyset {
+            order_by: vec![OrderBy::asc("id")],
+            cursor,
+            per_page: 10,
+        };
+        let ids = |page: &KeysetPage<User>| {
+            page.items
+                .iter()
+                .map(|u| u.id.unwrap())
+                .collect::<Vec<i32>>()
+        };
+
+        // Walk forward
+        let first =
+            backends::ModelBackend::paginate_keyset::<User>(&backend, query.clone(), keyset(None))
+                .unwrap();
+        assert_eq!(ids(&first), (3..=12).collect::<Vec<i32>>());
+        assert!(first.prev_cursor.is_none());
+
+        let second = backends::ModelBackend::paginate_keyset::<User>(
+            &backend,
+            query.clone(),
+            keyset(first.next_cursor.clone()),
+        )
+        .unwrap();
+        assert_eq!(ids(&second), (13..=22).collect::<Vec<i32>>());
+
+        let third = backends::ModelBackend::paginate_keyset::<User>(
+            &backend,
+            query.clone(),
+            keyset(second.next_cursor.clone()),
+        )
+        .unwrap();
+        assert_eq!(ids(&third), vec![23, 24, 25]);
+        assert!(third.next_cursor.is_none());
+
+        // Walk backward
+        let back = backends::ModelBackend::paginate_keyset::<User>(
+            &backend,
+            query.clone(),
+            keyset(third.prev_cursor.clone()),
+        )
+        .unwrap();
+        assert_eq!(ids(&back), ids(&second));
+
+        let back = backends::ModelBackend::paginate_keyset::<User>(
+            &backend,
+            query.clone(),
+            keyset(back.prev_cursor.clone()),
+        )
+        .unwrap();
+        assert_eq!(ids(&back), ids(&first));
+        assert!(back.prev_cursor.is_none());
+        assert!(back.next_cursor.is_some());
+
+        // Qualified key column is read from unqualified result column
+        let qualified = |cursor: Option<String>| Keyset {
+            order_by: vec![OrderBy::asc("users.id")],
+            cursor,
+            per_page: 10,
+        };
+        let first = backends::ModelBackend::paginate_keyset::<User>(
+            &backend,
+            query.clone(),
+            qualified(None),
+        )
+        .unwrap();
+        let second = backends::ModelBackend::paginate_keyset::<User>(
+            &backend,
+            query.clone(),
+            qualified(first.next_cursor),
+        )
+        .unwrap();
+        assert_eq!(ids(&second), (13..=22).collect::<Vec<i32>>());
+
+        // NULL keys are rejected instead of ending pagination
+        Backend::execute_raw(
+            &backend,
+            "UPDATE users SET is_active = NULL",
+            RawParams::none(),
+        )
+        .unwrap();
+        let nullable = Keyset {
+            order_by: vec![OrderBy::asc("is_active"), OrderBy::asc("id")],
+            cursor: None,
+            per_page: 10,
+        };
+        let error = match backends::ModelBackend::paginate_keyset::<User>(&backend, query, nullable)
+        {
+            Ok(_) => panic!("NULL key was accepted"),
+            Err(e) => e.to_string(),
+        };
+        assert_eq!(
+            error,
+            "Keyset column is_active is NULL, keys must be NOT NULL"
+        );
+        let cursor = Cursor::After(vec![EzqlValue::Null(), 1.into()]).encode();
+        assert!(backends::ModelBackend::paginate_keyset::<User>(
+            &backend,
+            SelectQueryParams::default(),
+            Keyset {
+                order_by: vec![OrderBy::asc("is_active"), OrderBy::asc("id")],
+                cursor: Some(cursor),
+                per_page: 10,
+            },
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_keyset_pagination_mixed_order_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        backends::ModelBackend::create_table::<Setting>(&backend, true).unwrap();
+        let settings = [
+            setting("a", "", 1),
+            setting("b", "", 2),
+            setting("c", "", 2),
+            setting("d", "", 1),
+            setting("e", "", 3),
+        ];
+        backends::ModelBackend::insert::<Setting>(&backend, &settings.iter().collect::<Vec<_>>())
+            .unwrap();
+
+        let keyset = |cursor: Option<String>| Keyset {
+            order_by: vec![OrderBy::desc("version"), OrderBy::asc("key")],
+            cursor,
+            per_page: 2,
+        };
+        let mut keys = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = backends::ModelBackend::paginate_keyset::<Setting>(
+                &backend,
+                SelectQueryParams::default(),
+                keyset(cursor),
+            )
+            .unwrap();
+            keys.extend(page.items.into_iter().map(|s| s.key));
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(keys, vec!["e", "b", "c", "a", "d"]);
+
+        // Same direction uses row value comparison
+        let query = SqliteDialect::select(
+            &Setting::get_table(),
+            SelectQueryParams {
+                where_clause: Some(WhereClause::RowGt(
+                    vec!["version".to_string(), "id".to_string()],
+                    vec![1.into(), 4.into()],
+                )),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            query.sql,
+            "SELECT * FROM settings WHERE (version, id) > (?, ?);"
+        );
+    }
+
+    // ====< Ordering >====
+    #[test]
+    fn test_order_by_sqlite_dialect() {
+        let order_by = OrderBy::desc("bio").collate("NOCASE").nulls_last();
+        assert_eq!(
+            SqliteDialect::order_by_term(order_by.clone(), true),
+            "bio COLLATE NOCASE DESC NULLS LAST"
+        );
+        assert_eq!(
+            SqliteDialect::order_by_term(order_by.clone(), false),
+            "(bio) IS NULL ASC, bio COLLATE NOCASE DESC"
+        );
+        assert_eq!(
+            order_by.reversed(),
+            OrderBy::asc("bio").collate("NOCASE").nulls_first()
+        );
+    }
+
+    fn insert_profiles(backend: &SqliteBackend) {
+        backends::ModelBackend::create_table::<Profile>(backend, true).unwrap();
+        let profile = |nickname: &str, bio: Option<&str>| Profile {
+            id: None,
+            nickname: nickname.to_string(),
+            bio: bio.map(str::to_string),
+        };
+        let profiles = [
+            profile("bob", Some("b")),
+            profile("Carl", None),
+            profile("al", Some("B")),
+            profile("dan", Some("a")),
+            profile("Eve", None),
+        ];
+        backends::ModelBackend::insert::<Profile>(backend, &profiles.iter().collect::<Vec<_>>())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_order_by_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_profiles(&backend);
+        let nicknames = |order_by: Vec<OrderBy>| {
+            backends::ModelBackend::select::<Profile>(
+                &backend,
+                SelectQueryParams {
+                    order_by,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+            .into_iter()
+            .map(|p| p.nickname)
+            .collect::<Vec<String>>()
+        };
+
+        // Multiple columns, case insensitive, NULLs last
+        assert_eq!(
+            nicknames(vec![
+                OrderBy::asc("bio").collate("NOCASE").nulls_last(),
+                OrderBy::desc("nickname"),
+            ]),
+            vec!["dan", "bob", "al", "Eve", "Carl"]
+        );
+        // NULLs first in descending order
+        assert_eq!(
+            nicknames(vec![OrderBy::desc("bio").nulls_first(), OrderBy::asc("id")]),
+            vec!["Carl", "Eve", "bob", "dan", "al"]
+        );
+        // Expressions
+        assert_eq!(
+            nicknames(vec![
+                OrderBy::desc("length(nickname)"),
+                OrderBy::asc("lower(nickname)"),
+            ]),
+            vec!["Carl", "bob", "dan", "Eve", "al"]
+        );
+    }
+
+    // ====< Where clause operators >====
+    fn select_nicknames(backend: &SqliteBackend, where_clause: WhereClause) -> Vec<String> {
+        backends::ModelBackend::select::<Profile>(
+            backend,
+            SelectQueryParams {
+                where_clause: Some(where_clause),
+                order_by: vec![OrderBy::asc("id")],
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .into_iter()
+        .map(|p| p.nickname)
+        .collect()
+    }
+
+    #[test]
+    fn test_where_operators_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_profiles(&backend);
+
+        assert_eq!(
+            select_nicknames(
+                &backend,
+                WhereClause::Between("id".to_string(), 2.into(), 4.into())
+            ),
+            vec!["Carl", "al", "dan"]
+        );
+        assert_eq!(
+            select_nicknames(
+                &backend,
+                WhereClause::ILike("nickname".to_string(), "CA%".into())
+            ),
+            vec!["Carl"]
+        );
+        assert_eq!(
+            select_nicknames(
+                &backend,
+                WhereClause::Glob("nickname".to_string(), "[a-c]*".into())
+            ),
+            vec!["bob", "al"]
+        );
+        assert_eq!(
+            select_nicknames(
+                &backend,
+                WhereClause::CompareColumns(
+                    "nickname".to_string(),
+                    CompareOp::Gt,
+                    "bio".to_string()
+                )
+            ),
+            vec!["bob", "al", "dan"]
+        );
+    }
+
+    #[test]
+    fn test_regexp_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_profiles(&backend);
+
+        assert_eq!(
+            select_nicknames(
+                &backend,
+                WhereClause::Regexp("nickname".to_string(), "^[A-Z][a-z]+$".into())
+            ),
+            vec!["Carl", "Eve"]
+        );
+        // NULL values never match
+        assert_eq!(
+            select_nicknames(
+                &backend,
+                WhereClause::Regexp("bio".to_string(), "^[a-z]$".into())
+            ),
+            vec!["bob", "dan"]
+        );
+        // Invalid pattern is reported as error
+        assert!(backends::ModelBackend::select::<Profile>(
+            &backend,
+            SelectQueryParams {
+                where_clause: Some(WhereClause::Regexp("bio".to_string(), "(".into())),
+                ..Default::default()
+            },
+        )
+        .is_err());
+    }
+
+    // Users named after profiles bob (active) and Eve
+    fn insert_profile_users(backend: &SqliteBackend) {
+        backends::ModelBackend::create_table::<User>(backend, true).unwrap();
+        let users = [
+            User {
+                id: None,
+                name: Some("bob".to_string()),
+                is_active: Some(true),
+            },
+            User {
+                id: None,
+                name: Some("Eve".to_string()),
+                is_active: Some(false),
+            },
+        ];
+        backends::ModelBackend::insert::<User>(backend, &users.iter().collect::<Vec<_>>()).unwrap();
+    }
+
+    #[test]
+    fn test_exists_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_profiles(&backend);
+        insert_profile_users(&backend);
+
+        let has_user = |active: Option<bool>| {
+            let mut clauses = vec![WhereClause::CompareColumns(
+                "users.name".to_string(),
+                CompareOp::Eq,
+                "profiles.nickname".to_string(),
+            )];
+            if let Some(active) = active {
+                clauses.push(WhereClause::Eq("is_active".to_string(), active.into()));
+            }
+            WhereClause::Exists(Box::new(Subquery::model::<User>(SelectQueryParams {
+                where_clause: Some(WhereClause::And(clauses)),
+                ..Default::default()
+            })))
+        };
+
+        assert_eq!(
+            select_nicknames(&backend, has_user(None)),
+            vec!["bob", "Eve"]
+        );
+        // Parameters of outer query and subquery keep their order
+        assert_eq!(
+            select_nicknames(
+                &backend,
+                WhereClause::And(vec![
+                    WhereClause::Gt("id".to_string(), 0.into()),
+                    has_user(Some(false)),
+                    WhereClause::Ne("nickname".to_string(), "bob".into()),
+                ])
+            ),
+            vec!["Eve"]
+        );
+        assert_eq!(
+            select_nicknames(&backend, WhereClause::Not(Box::new(has_user(None)))),
+            vec!["Carl", "al", "dan"]
+        );
+        // Negated subquery keeps its parameters
+        let not_exists = SqliteDialect::translate_where_clause(WhereClause::Not(Box::new(
+            has_user(Some(false)),
+        )));
+        assert!(not_exists.sql.starts_with("NOT (EXISTS ("));
+        assert_eq!(not_exists.params, vec![false.into()]);
+        assert_eq!(
+            select_nicknames(
+                &backend,
+                WhereClause::And(vec![
+                    WhereClause::Not(Box::new(has_user(Some(false)))),
+                    WhereClause::Ne("nickname".to_string(), "al".into()),
+                ])
+            ),
+            vec!["bob", "Carl", "dan"]
+        );
+    }
+
+    // ====< Subqueries >====
+    fn user_names(active: bool) -> Box<Subquery> {
+        Box::new(Subquery::model::<User>(SelectQueryParams {
+            columns: Some(vec!["name".into()]),
+            where_clause: Some(WhereClause::Eq("is_active".to_string(), active.into())),
+            ..Default::default()
+        }))
+    }
+
+    #[test]
+    fn test_where_subquery_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_profiles(&backend);
+        insert_profile_users(&backend);
+
+        let where_clause = WhereClause::And(vec![
+            WhereClause::Gt("id".to_string(), 0.into()),
+            WhereClause::InSubquery("nickname".to_string(), user_names(true)),
+            WhereClause::Ne("nickname".to_string(), "al".into()),
+        ]);
+        let query = SqliteDialect::translate_where_clause(where_clause.clone());
+        assert_eq!(
+            query.sql,
+            "(id > ?) AND (nickname IN (SELECT name FROM users WHERE is_active = ?)) AND (nickname != ?)"
+        );
+        assert_eq!(
+            query.params,
+            vec![0.into(), true.into(), EzqlValue::from("al")]
+        );
+        assert_eq!(select_nicknames(&backend, where_clause), vec!["bob"]);
+
+        assert_eq!(
+            select_nicknames(
+                &backend,
+                WhereClause::NotInSubquery("nickname".to_string(), user_names(true))
+            ),
+            vec!["Carl", "al", "dan", "Eve"]
+        );
+
+        // Scalar comparison
+        let user_count = Subquery::model::<User>(SelectQueryParams {
+            columns: Some(vec!["count(*)".into()]),
+            where_clause: Some(WhereClause::Ne("name".to_string(), "x".into())),
+            ..Default::default()
+        });
+        assert_eq!(
+            select_nicknames(
+                &backend,
+                WhereClause::CompareSubquery("id".to_string(), CompareOp::Le, Box::new(user_count))
+            ),
+            vec!["bob", "Carl"]
+        );
+    }
+
+    #[test]
+    fn test_projection_subquery_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_profiles(&backend);
+        insert_profile_users(&backend);
+
+        // Fill bio with name of matching active user
+        let active_name = Subquery::model::<User>(SelectQueryParams {
+            columns: Some(vec!["name".into()]),
+            where_clause: Some(WhereClause::And(vec![
+                WhereClause::CompareColumns(
+                    "users.name".to_string(),
+                    CompareOp::Eq,
+                    "profiles.nickname".to_string(),
+                ),
+                WhereClause::Eq("is_active".to_string(), true.into()),
+            ])),
+            ..Default::default()
+        });
+        let query = SelectQueryParams {
+            columns: Some(vec![
+                "id".into(),
+                "nickname".into(),
+                Projection::Subquery(Box::new(active_name), "bio".to_string()),
+            ]),
+            where_clause: Some(WhereClause::Lt("id".to_string(), 3.into())),
+            ..Default::default()
+        };
+
+        let select = SqliteDialect::select(&Profile::get_table(), query.clone());
+        assert_eq!(
+            select.sql,
+            "SELECT id, nickname, (SELECT name FROM users WHERE (users.name = profiles.nickname) AND (is_active = ?)) AS bio FROM profiles WHERE id < ?;"
+        );
+        assert_eq!(select.params, vec![true.into(), 3.into()]);
+
+        let profiles = backends::ModelBackend::select::<Profile>(&backend, query).unwrap();
+        assert_eq!(
+            profiles,
+            vec![
+                Profile {
+                    id: Some(1),
+                    nickname: "bob".to_string(),
+                    bio: Some("bob".to_string()),
+                },
+                Profile {
+                    id: Some(2),
+                    nickname: "Carl".to_string(),
+                    bio: None,
+                },
+            ]
+        );
+    }
+
+    // ====< Expressions >====
+    #[test]
+    fn test_expr_sqlite_dialect() {
+        let query = SqliteDialect::update(
+            &Setting::get_table(),
+            UpdateQueryParams {
+                set: vec![
+                    ("version".to_string(), (Expr::column("version") - 1) * 2),
+                    ("value".to_string(), Expr::column("value").concat("!")),
+                ],
+                where_clause: Some(WhereClause::Compare(
+                    Expr::column("key").lower(),
+                    CompareOp::Eq,
+                    "theme".into(),
+                )),
+            },
+        );
+        assert_eq!(
+            query.sql,
+            "UPDATE settings SET version = ((version - ?) * ?), value = (value || ?) WHERE lower(key) = ?;"
+        );
+        assert_eq!(
+            query.params,
+            vec![1.into(), 2.into(), "!".into(), EzqlValue::from("theme")]
+        );
+
+        // CASE without ELSE
+        let query = SqliteDialect::update(
+            &Setting::get_table(),
+            UpdateQueryParams {
+                set: vec![(
+                    "value".to_string(),
+                    Expr::case()
+                        .when(WhereClause::Gt("version".to_string(), 1.into()), "new")
+                        .end(),
+                )],
+                where_clause: None,
+            },
+        );
+        assert_eq!(
+            query.sql,
+            "UPDATE settings SET value = CASE WHEN version > ? THEN ? END;"
+        );
+    }
+
+    #[test]
+    fn test_expr_update_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_settings(&backend);
+
+        let updated = backends::ModelBackend::update::<Setting>(
+            &backend,
+            UpdateQueryParams {
+                set: vec![("version".to_string(), Expr::column("version") + 10)],
+                where_clause: Some(WhereClause::Compare(
+                    Expr::column("key").upper(),
+                    CompareOp::Ne,
+                    "TZ".into(),
+                )),
+            },
+        )
+        .unwrap();
+        assert_eq!(updated, 2);
+        assert_eq!(select_setting(&backend, "theme").version, 11);
+        assert_eq!(select_setting(&backend, "lang").version, 11);
+        assert_eq!(select_setting(&backend, "tz").version, 2);
+    }
+
+    #[test]
+    fn test_expr_projection_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_profiles(&backend);
+
+        let profiles = backends::ModelBackend::select::<Profile>(
+            &backend,
+            SelectQueryParams {
+                columns: Some(vec![
+                    "id".into(),
+                    Projection::Expr(
+                        Expr::case()
+                            .when(WhereClause::Gt("id".to_string(), 3.into()), "late")
+                            .otherwise(Expr::column("nickname")),
+                        "nickname".to_string(),
+                    ),
+                    Projection::Expr(
+                        Expr::coalesce(vec![
+                            Expr::column("bio"),
+                            Expr::column("id").cast(EzqlType::VarChar(255)),
+                        ]),
+                        "bio".to_string(),
+                    ),
+                ]),
+                // |length(nickname) - 3| = 1 or last profile
+                where_clause: Some(WhereClause::Or(vec![
+                    WhereClause::Compare(
+                        (Expr::column("nickname").length() - 3).abs(),
+                        CompareOp::Eq,
+                        1.into(),
+                    ),
+                    WhereClause::Eq("id".to_string(), 5.into()),
+                ])),
+                order_by: vec![OrderBy::asc("id")],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            profiles
+                .into_iter()
+                .map(|p| (p.nickname, p.bio.unwrap()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Carl".to_string(), "2".to_string()),
+                ("al".to_string(), "B".to_string()),
+                ("late".to_string(), "5".to_string()),
+            ]
+        );
+    }
+
+    // ====< Common table expressions >====
+    struct Category {
+        id: Option<i32>,
+        parent_id: Option<i32>,
+        name: String,
+    }
+
+    impl EzqlModelTrait for Category {
+        fn get_table() -> Table {
+            Table {
+                name: "categories".to_string(),
+                columns: vec![
+                    Column::new::<Option<i32>>("id", vec![ColumnProperty::PrimaryKey]),
+                    Column::new::<Option<i32>>("parent_id", vec![]),
+                    Column::new::<String>("name", vec![]),
+                ],
+            }
+        }
+
+        fn as_column_values(&self) -> Vec<Option<EzqlValue>> {
+            vec![
+                self.id.as_ref().map(ToEzql::to_ezql),
+                Some(self.parent_id.to_ezql()),
+                Some(self.name.to_ezql()),
+            ]
+        }
+
+        fn from_column_values(
+            values: Vec<Option<EzqlValue>>,
+        ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(Self {
+                id: decode_column(&values, 0)?,
+                parent_id: decode_column(&values, 1)?,
+                name: decode_column(&values, 2)?,
+            })
+        }
+    }
+
+    // Rows of the recursive `tree` CTE
+    #[derive(Debug, PartialEq)]
+    struct TreeNode {
+        id: i32,
+        name: String,
+        depth: i32,
+    }
+
+    impl EzqlModelTrait for TreeNode {
+        fn get_table() -> Table {
+            Table {
+                name: "tree".to_string(),
+                columns: vec![
+                    Column::new::<i32>("id", vec![]),
+                    Column::new::<String>("name", vec![]),
+                    Column::new::<i32>("depth", vec![]),
+                ],
+            }
+        }
+
+        fn as_column_values(&self) -> Vec<Option<EzqlValue>> {
+            vec![
+                Some(self.id.to_ezql()),
+                Some(self.name.to_ezql()),
+                Some(self.depth.to_ezql()),
+            ]
+        }
+
+        fn from_column_values(
+            values: Vec<Option<EzqlValue>>,
+        ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(Self {
+                id: decode_column(&values, 0)?,
+                name: decode_column(&values, 1)?,
+                depth: decode_column(&values, 2)?,
+            })
+        }
+    }
+
+    fn insert_categories(backend: &SqliteBackend) {
+        backends::ModelBackend::create_table::<Category>(backend, true).unwrap();
+        let category = |parent_id: Option<i32>, name: &str| Category {
+            id: None,
+            parent_id,
+            name: name.to_string(),
+        };
+        let categories = [
+            category(None, "root"),
+            category(Some(1), "books"),
+            category(Some(1), "music"),
+            category(Some(2), "fiction"),
+            category(Some(4), "scifi"),
+            category(Some(3), "jazz"),
+        ];
+        backends::ModelBackend::insert::<Category>(backend, &categories.iter().collect::<Vec<_>>())
+            .unwrap();
+    }
+
+    // Subtree of category with depth relative to it
+    fn subtree(root: i32) -> Cte {
+        let anchor = Subquery::model::<Category>(SelectQueryParams {
+            columns: Some(vec![
+                "id".into(),
+                "name".into(),
+                Projection::Expr(Expr::value(0), "depth".to_string()),
+            ]),
+            where_clause: Some(WhereClause::Eq("id".to_string(), root.into())),
+            ..Default::default()
+        });
+        let recursive = Subquery::model::<Category>(SelectQueryParams {
+            columns: Some(vec![
+                "categories.id".into(),
+                "categories.name".into(),
+                Projection::Expr(Expr::column("tree.depth") + 1, "depth".to_string()),
+            ]),
+            joins: vec![Join::inner(
+                "tree",
+                WhereClause::CompareColumns(
+                    "categories.parent_id".to_string(),
+                    CompareOp::Eq,
+                    "tree.id".to_string(),
+                ),
+            )],
+            ..Default::default()
+        });
+        Cte::recursive("tree", anchor, recursive).columns(vec!["id", "name", "depth"])
+    }
+
+    #[test]
+    fn test_recursive_cte_sqlite_dialect() {
+        let query = SqliteDialect::select(
+            &TreeNode::get_table(),
+            SelectQueryParams {
+                where_clause: Some(WhereClause::Le("depth".to_string(), 1.into())),
+                with: vec![subtree(2)],
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            query.sql,
+            "WITH RECURSIVE tree(id, name, depth) AS (\
+             SELECT id, name, ? AS depth FROM categories WHERE id = ? \
+             UNION ALL \
+             SELECT categories.id, categories.name, (tree.depth + ?) AS depth FROM categories \
+             INNER JOIN tree ON categories.parent_id = tree.id) \
+             SELECT * FROM tree WHERE depth <= ?;"
+        );
+        assert_eq!(
+            query.params,
+            vec![0.into(), 2.into(), 1.into(), EzqlValue::from(1)]
+        );
+    }
+
+    #[test]
+    fn test_recursive_cte_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_categories(&backend);
+
+        let node = |id: i32, name: &str, depth: i32| TreeNode {
+            id,
+            name: name.to_string(),
+            depth,
+        };
+        let nodes = backends::ModelBackend::select::<TreeNode>(
+            &backend,
+            SelectQueryParams {
+                order_by: vec![OrderBy::asc("depth"), OrderBy::asc("id")],
+                with: vec![subtree(2)],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            nodes,
+            vec![
+                node(2, "books", 0),
+                node(4, "fiction", 1),
+                node(5, "scifi", 2)
+            ]
+        );
+
+        // Whole tree, limited depth
+        let count = backends::ModelBackend::count::<TreeNode>(
+            &backend,
+            SelectQueryParams {
+                where_clause: Some(WhereClause::Le("depth".to_string(), 1.into())),
+                with: vec![subtree(1)],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn test_cte_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_categories(&backend);
+
+        // Delete leaves of the tree
+        let leaves = Cte::new(
+            "leaves",
+            Subquery::model::<Category>(SelectQueryParams {
+                columns: Some(vec!["id".into()]),
+                where_clause: Some(WhereClause::Not(Box::new(WhereClause::Exists(Box::new(
+                    Subquery::new(
+                        Table {
+                            name: "categories AS child".to_string(),
+                            columns: vec![],
+                        },
+                        SelectQueryParams {
+                            where_clause: Some(WhereClause::CompareColumns(
+                                "child.parent_id".to_string(),
+                                CompareOp::Eq,
+                                "categories.id".to_string(),
+                            )),
+                            ..Default::default()
+                        },
+                    ),
+                ))))),
+                ..Default::default()
+            }),
+        );
+        let deleted = backends::ModelBackend::delete::<Category>(
+            &backend,
+            SelectQueryParams {
+                where_clause: Some(WhereClause::InSubquery(
+                    "id".to_string(),
+                    Box::new(Subquery::new(
+                        Table {
+                            name: "leaves".to_string(),
+                            columns: vec![],
+                        },
+                        SelectQueryParams::default(),
+                    )),
+                )),
+                with: vec![leaves],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(deleted, 2);
+        assert_eq!(
+            backends::ModelBackend::count::<Category>(&backend, SelectQueryParams::default())
+                .unwrap(),
+            4
+        );
+    }
+
+    // ====< Window functions >====
+    #[derive(Debug, PartialEq)]
+    struct RankedSetting {
+        key: String,
+        rank: i32,
+    }
+
+    impl EzqlRecordTrait for RankedSetting {
+        fn from_values(
+            values: Vec<Option<EzqlValue>>,
+        ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(Self {
+                key: decode_column(&values, 0)?,
+                rank: decode_column(&values, 1)?,
+            })
+        }
+    }
+
+    #[test]
+    fn test_window_sqlite_dialect() {
+        let query = SqliteDialect::translate_expr(
+            Expr::column("id").sum().over(
+                Window::new()
+                    .partition_by(Expr::column("version"))
+                    .order_by(OrderBy::asc("key"))
+                    .rows(FrameBound::Preceding(2), FrameBound::CurrentRow),
+            ),
+        );
+        assert_eq!(
+            query.sql,
+            "sum(id) OVER (PARTITION BY version ORDER BY key ASC ROWS BETWEEN 2 PRECEDING AND CURRENT ROW)"
+        );
+    }
+
+    #[test]
+    fn test_window_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_settings(&backend);
+
+        let rows = Backend::select_as::<(String, i32, i32, Option<String>, Option<String>)>(
+            &backend,
+            &Setting::get_table(),
+            SelectQueryParams {
+                columns: Some(vec![
+                    "key".into(),
+                    Projection::Expr(
+                        Expr::row_number().over(
+                            Window::new()
+                                .partition_by(Expr::column("version"))
+                                .order_by(OrderBy::asc("key")),
+                        ),
+                        "position".to_string(),
+                    ),
+                    Projection::Expr(
+                        Expr::column("id").sum().over(
+                            Window::new()
+                                .partition_by(Expr::column("version"))
+                                .order_by(OrderBy::asc("key"))
+                                .rows(FrameBound::UnboundedPreceding, FrameBound::CurrentRow),
+                        ),
+                        "running_total".to_string(),
+                    ),
+                    Projection::Expr(
+                        Expr::column("key")
+                            .lag(1)
+                            .over(Window::new().order_by(OrderBy::asc("id"))),
+                        "previous".to_string(),
+                    ),
+                    Projection::Expr(
+                        Expr::column("key")
+                            .lead(1)
+                            .over(Window::new().order_by(OrderBy::asc("id"))),
+                        "next".to_string(),
+                    ),
+                ]),
+                order_by: vec![OrderBy::asc("id")],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let some = |key: &str| Some(key.to_string());
+        assert_eq!(
+            rows,
+            vec![
+                ("theme".to_string(), 2, 3, None, some("lang")),
+                ("lang".to_string(), 1, 2, some("theme"), some("tz")),
+                ("tz".to_string(), 1, 3, some("lang"), None),
+            ]
+        );
+
+        // Ad-hoc struct
+        let ranked = Backend::select_as::<RankedSetting>(
+            &backend,
+            &Setting::get_table(),
+            SelectQueryParams {
+                columns: Some(vec![
+                    "key".into(),
+                    Projection::Expr(
+                        Expr::rank().over(Window::new().order_by(OrderBy::desc("version"))),
+                        "rank".to_string(),
+                    ),
+                ]),
+                order_by: vec![OrderBy::asc("id")],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let ranked_setting = |key: &str, rank: i32| RankedSetting {
+            key: key.to_string(),
+            rank,
+        };
+        assert_eq!(
+            ranked,
+            vec![
+                ranked_setting("theme", 2),
+                ranked_setting("lang", 2),
+                ranked_setting("tz", 1),
+            ]
+        );
+
+        // Decoding errors name projected column
+        let error = Backend::select_as::<(String, bool)>(
+            &backend,
+            &Setting::get_table(),
+            SelectQueryParams {
+                columns: Some(vec!["key".into(), "value".into()]),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        let error = error.downcast_ref::<DecodeError>().unwrap();
+        assert_eq!(error.column.as_deref(), Some("value"));
+    }
+
+    // ====< Compound selects >====
+    fn profile_names(where_clause: WhereClause) -> Subquery {
+        Subquery::model::<Profile>(SelectQueryParams {
+            columns: Some(vec!["nickname".into()]),
+            where_clause: Some(where_clause),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_compound_select_sqlite_dialect() {
+        let mut compound =
+            CompoundSelect::new(profile_names(WhereClause::Gt("id".to_string(), 1.into())))
+                .union_all(Subquery::model::<User>(SelectQueryParams {
+                    columns: Some(vec!["name".into()]),
+                    where_clause: Some(WhereClause::Eq("is_active".to_string(), true.into())),
+                    order_by: vec![OrderBy::desc("id")],
+                    limit: Some(1),
+                    ..Default::default()
+                }))
+                .except(profile_names(WhereClause::Eq("id".to_string(), 3.into())));
+        compound.order_by = vec![OrderBy::asc("nickname")];
+        compound.limit = Some(10);
+
+        let query = SqliteDialect::compound_select(compound);
+        assert_eq!(
+            query.sql,
+            "SELECT nickname FROM profiles WHERE id > ? \
+             UNION ALL SELECT * FROM (SELECT name FROM users WHERE is_active = ? ORDER BY id DESC LIMIT 1) \
+             EXCEPT SELECT nickname FROM profiles WHERE id = ? \
+             ORDER BY nickname ASC LIMIT 10;"
+        );
+        assert_eq!(query.params, vec![1.into(), true.into(), 3.into()]);
     }
 
     #[test]
-    fn test_drop_sqlite_backend() {
+    fn test_compound_select_sqlite_backend() {
         let backend = SqliteBackend::new_in_memory();
-        backends::ModelBackend::create_table::<User>(&backend, true).unwrap();
-        backends::ModelBackend::drop_table::<User>(&backend, true).unwrap();
-
-        // Insert should fail because table does not exist
-        let user = User {
-            id: None,
-            name: Some("John".to_string()),
-            is_active: Some(true),
+        insert_profiles(&backend);
+        insert_profile_users(&backend);
+        let user_names = || {
+            Subquery::model::<User>(SelectQueryParams {
+                columns: Some(vec!["name".into()]),
+                ..Default::default()
+            })
+        };
+        let names = |mut compound: CompoundSelect| {
+            compound.order_by = vec![OrderBy::asc("nickname")];
+            Backend::select_compound(&backend, compound)
+                .unwrap()
+                .into_iter()
+                .map(|row| String::from_ezql(row[0].clone().unwrap()).unwrap())
+                .collect::<Vec<String>>()
         };
 
-        assert!(backends::ModelBackend::insert::<User>(&backend, &[&user]).is_err());
+        assert_eq!(
+            names(CompoundSelect::new(profile_names(WhereClause::All)).intersect(user_names())),
+            vec!["Eve", "bob"]
+        );
+        assert_eq!(
+            names(CompoundSelect::new(profile_names(WhereClause::All)).except(user_names())),
+            vec!["Carl", "al", "dan"]
+        );
+        assert_eq!(
+            names(
+                CompoundSelect::new(profile_names(WhereClause::Lt("id".to_string(), 3.into())))
+                    .union(user_names())
+            ),
+            vec!["Carl", "Eve", "bob"]
+        );
+
+        // Models, columns in other order than table
+        let profiles = |where_clause: WhereClause| {
+            Subquery::model::<Profile>(SelectQueryParams {
+                columns: Some(vec!["nickname".into(), "bio".into(), "id".into()]),
+                where_clause: Some(where_clause),
+                ..Default::default()
+            })
+        };
+        let mut compound =
+            CompoundSelect::new(profiles(WhereClause::Eq("id".to_string(), 1.into())))
+                .union(profiles(WhereClause::Ge("id".to_string(), 4.into())));
+        compound.order_by = vec![OrderBy::desc("id")];
+        compound.limit = Some(2);
+        let profiles =
+            backends::ModelBackend::select_compound::<Profile>(&backend, compound).unwrap();
+        assert_eq!(
+            profiles,
+            vec![
+                Profile {
+                    id: Some(5),
+                    nickname: "Eve".to_string(),
+                    bio: None,
+                },
+                Profile {
+                    id: Some(4),
+                    nickname: "dan".to_string(),
+                    bio: Some("a".to_string()),
+                },
+            ]
+        );
     }
 
+    // ====< Distinct and aliases >====
     #[test]
-    fn test_insert_sqlite_backend() {
+    fn test_distinct_sqlite_backend() {
         let backend = SqliteBackend::new_in_memory();
-        let user = User {
-            id: None,
-            name: Some("John".to_string()),
-            is_active: Some(true),
-        };
-        backends::ModelBackend::create_table::<User>(&backend, true).unwrap();
-        backends::ModelBackend::insert::<User>(&backend, &[&user]).unwrap();
+        insert_settings(&backend);
+
+        let versions = Backend::select_as::<(i32,)>(
+            &backend,
+            &Setting::get_table(),
+            SelectQueryParams {
+                columns: Some(vec!["version".into()]),
+                order_by: vec![OrderBy::asc("version")],
+                distinct: Some(Distinct::Rows),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(versions, vec![(1,), (2,)]);
     }
 
     #[test]
-    fn test_select_sqlite_backend() {
+    fn test_paginate_distinct_sqlite_backend() {
         let backend = SqliteBackend::new_in_memory();
-        let user = User {
-            id: None,
-            name: Some("John".to_string()),
-            is_active: Some(true),
+        insert_settings(&backend);
+
+        // Count respects DISTINCT on selected columns
+        let versions = SelectQueryParams {
+            columns: Some(vec!["version".into()]),
+            distinct: Some(Distinct::Rows),
+            ..Default::default()
         };
-        backends::ModelBackend::create_table::<User>(&backend, true).unwrap();
-        backends::ModelBackend::insert::<User>(&backend, &[&user]).unwrap();
-        let users = backends::ModelBackend::select::<User>(
+        let count = SqliteDialect::count(&Setting::get_table(), versions.clone());
+        assert_eq!(
+            count.sql,
+            "SELECT COUNT(*) FROM (SELECT DISTINCT version FROM settings);"
+        );
+        assert_eq!(
+            Backend::count(&backend, &Setting::get_table(), versions).unwrap(),
+            2
+        );
+
+        // Total counts one row per DISTINCT ON group
+        let page = backends::ModelBackend::paginate::<Setting>(
             &backend,
             SelectQueryParams {
-                columns: Some(vec!["name".to_string()]),
-                where_clause: None,
-                order_by: None,
-                limit: None,
-                offset: None,
+                order_by: vec![OrderBy::asc("version"), OrderBy::asc("key")],
+                distinct: Some(Distinct::On(vec![Expr::column("version")])),
+                ..Default::default()
             },
+            1,
+            1,
         )
         .unwrap();
-        assert_eq!(users.len(), 1);
-        assert_eq!(users[0].name, Some("John".to_string()));
+        assert_eq!(page.total, 2);
+        assert_eq!(page.total_pages(), 2);
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].key, "lang");
     }
 
     #[test]
-    fn test_delete_sqlite_backend() {
+    fn test_distinct_on_sqlite_backend() {
         let backend = SqliteBackend::new_in_memory();
-        let user = User {
-            id: None,
-            name: Some("John".to_string()),
-            is_active: Some(true),
+        insert_settings(&backend);
+        let query = |order_by: Vec<OrderBy>| SelectQueryParams {
+            columns: Some(vec![
+                "key".into(),
+                Projection::Expr(Expr::column("version") * 10, "version".to_string()),
+            ]),
+            where_clause: Some(WhereClause::Ne("key".to_string(), "none".into())),
+            order_by,
+            distinct: Some(Distinct::On(vec![Expr::column("version")])),
+            ..Default::default()
         };
-        backends::ModelBackend::create_table::<User>(&backend, true).unwrap();
-        backends::ModelBackend::insert::<User>(&backend, &[&user]).unwrap();
-        let users = backends::ModelBackend::select::<User>(
+
+        let select = SqliteDialect::select(
+            &Setting::get_table(),
+            query(vec![OrderBy::asc("version"), OrderBy::asc("key")]),
+        );
+        assert_eq!(
+            select.sql,
+            "SELECT key, (version * ?) AS version FROM (\
+             SELECT *, row_number() OVER (PARTITION BY version ORDER BY version ASC, key ASC) AS ezql_distinct_row \
+             FROM settings WHERE key != ?) AS settings \
+             WHERE ezql_distinct_row = 1 ORDER BY version ASC, key ASC;"
+        );
+        assert_eq!(select.params, vec![10.into(), EzqlValue::from("none")]);
+
+        let first = |order_by: Vec<OrderBy>| {
+            Backend::select_as::<(String, i32)>(&backend, &Setting::get_table(), query(order_by))
+                .unwrap()
+        };
+        assert_eq!(
+            first(vec![OrderBy::asc("version"), OrderBy::asc("key")]),
+            vec![("lang".to_string(), 10), ("tz".to_string(), 20)]
+        );
+        assert_eq!(
+            first(vec![OrderBy::desc("version"), OrderBy::desc("key")]),
+            vec![("tz".to_string(), 20), ("theme".to_string(), 10)]
+        );
+    }
+
+    #[test]
+    fn test_select_named_rows_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_settings(&backend);
+
+        let rows = Backend::select(
             &backend,
+            &Setting::get_table(),
             SelectQueryParams {
-                columns: Some(vec!["name".to_string()]),
-                where_clause: None,
-                order_by: None,
-                limit: None,
-                offset: None,
+                columns: Some(vec![
+                    Projection::alias("key", "name"),
+                    Projection::Expr(Expr::column("version") + 1, "next_version".to_string()),
+                    Projection::Expr(Expr::value(EzqlValue::Null()), "missing".to_string()),
+                ]),
+                where_clause: Some(WhereClause::Eq("key".to_string(), "tz".into())),
+                ..Default::default()
             },
         )
         .unwrap();
-        assert_eq!(users.len(), 1);
-        assert_eq!(users[0].name, Some("John".to_string()));
+        assert_eq!(rows.len(), 1);
+        let row = &rows[0];
+        assert_eq!(row.column_names(), ["name", "next_version", "missing"]);
+        assert_eq!(row.get::<String>("name").unwrap(), "tz");
+        assert_eq!(row.get::<i32>("next_version").unwrap(), 3);
+        assert_eq!(row.get_index::<i32>(1).unwrap(), 3);
+        assert_eq!(row.get::<Option<i32>>("missing").unwrap(), None);
+        assert_eq!(row.value("name"), Some(&EzqlValue::from("tz")));
+        assert!(row.get::<String>("key").is_err());
+        assert!(row.get::<i32>("name").is_err());
+    }
 
-        backends::ModelBackend::delete::<User>(
+    // ====< Named rows >====
+    #[test]
+    fn test_projection_order_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_profiles(&backend);
+        let query = || SelectQueryParams {
+            columns: Some(vec!["bio".into(), "nickname".into(), "id".into()]),
+            where_clause: Some(WhereClause::Le("id".to_string(), 2.into())),
+            order_by: vec![OrderBy::asc("id")],
+            ..Default::default()
+        };
+        let expected = vec![
+            Profile {
+                id: Some(1),
+                nickname: "bob".to_string(),
+                bio: Some("b".to_string()),
+            },
+            Profile {
+                id: Some(2),
+                nickname: "Carl".to_string(),
+                bio: None,
+            },
+        ];
+
+        assert_eq!(
+            backends::ModelBackend::select::<Profile>(&backend, query()).unwrap(),
+            expected
+        );
+        let mut stmt = backend.select_iter::<Profile>(query()).unwrap();
+        assert_eq!(
+            stmt.iter()
+                .unwrap()
+                .collect::<Result<Vec<Profile>, _>>()
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_row_metadata_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_settings(&backend);
+
+        let rows = Backend::select(
             &backend,
+            &Setting::get_table(),
             SelectQueryParams {
-                columns: None,
-                where_clause: Some(WhereClause::All),
-                order_by: None,
-                limit: None,
-                offset: None,
+                columns: Some(vec![
+                    "version".into(),
+                    "key".into(),
+                    Projection::Expr(Expr::column("key").upper(), "upper_key".to_string()),
+                ]),
+                order_by: vec![OrderBy::asc("id")],
+                limit: Some(1),
+                ..Default::default()
             },
         )
         .unwrap();
+        let row = &rows[0];
+        assert_eq!(
+            row.columns(),
+            [
+                RowColumn::new("version", Some(EzqlType::Integer())),
+                RowColumn::new("key", Some(EzqlType::VarChar(255))),
+                RowColumn::new("upper_key", None),
+            ]
+        );
+        assert_eq!(row["key"], Some("theme".into()));
+        assert_eq!(row[0], Some(1.into()));
+        assert_eq!(
+            Setting::from_row(row, &Setting::get_table())
+                .unwrap_err()
+                .to_string(),
+            ConversionError::new("String", EzqlValue::Null())
+                .at_column(2)
+                .to_string()
+        );
+    }
 
-        let users = backends::ModelBackend::select::<User>(
+    #[test]
+    fn test_paginate_keeps_query_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_categories(&backend);
+
+        // Pages of CTE rows
+        let page = backends::ModelBackend::paginate::<TreeNode>(
             &backend,
             SelectQueryParams {
-                columns: Some(vec!["name".to_string()]),
-                where_clause: None,
-                order_by: None,
-                limit: None,
-                offset: None,
+                order_by: vec![OrderBy::asc("id")],
+                with: vec![subtree(1)],
+                ..Default::default()
             },
+            2,
+            4,
         )
         .unwrap();
-        assert_eq!(users.len(), 0);
+        assert_eq!(page.total, 6);
+        assert_eq!(
+            page.items.iter().map(|n| n.id).collect::<Vec<i32>>(),
+            vec![5, 6]
+        );
     }
 
+    // ====< Raw SQL >====
     #[test]
-    fn test_update_sqlite_backend() {
+    fn test_raw_sql_sqlite_backend() {
         let backend = SqliteBackend::new_in_memory();
-        let user = User {
-            id: None,
-            name: Some("John".to_string()),
-            is_active: Some(true),
-        };
-        backends::ModelBackend::create_table::<User>(&backend, true).unwrap();
-        backends::ModelBackend::insert::<User>(&backend, &[&user]).unwrap();
-        let users = backends::ModelBackend::select::<User>(
+        insert_settings(&backend);
+
+        let updated = Backend::execute_raw(
             &backend,
-            SelectQueryParams {
-                columns: Some(vec!["name".to_string()]),
-                where_clause: None,
-                order_by: None,
-                limit: None,
-                offset: None,
-            },
+            "UPDATE settings SET value = upper(value) WHERE version = ?",
+            RawParams::Positional(vec![1.into()]),
         )
         .unwrap();
-        assert_eq!(users.len(), 1);
-        assert_eq!(users[0].name, Some("John".to_string()));
+        assert_eq!(updated, 2);
 
-        backends::ModelBackend::update::<User>(
+        let rows = Backend::query_raw(
             &backend,
-            UpdateQueryParams {
+            "SELECT key, value || :suffix AS tagged FROM settings WHERE version = :version ORDER BY id",
+            RawParams::named(vec![("version", 1.into()), (":suffix", "!".into())]),
+        )
+        .unwrap();
+        assert_eq!(
+            rows.iter()
+                .map(|row| row.get::<String>("tagged").unwrap())
+                .collect::<Vec<String>>(),
+            vec!["DARK!", "EN!"]
+        );
+
+        let settings = backends::ModelBackend::query_as::<Setting>(
+            &backend,
+            "SELECT version, key, value FROM settings WHERE key = ?1 OR key = ?2 ORDER BY key",
+            RawParams::Positional(vec!["tz".into(), "lang".into()]),
+        )
+        .unwrap();
+        assert_eq!(
+            settings
+                .iter()
+                .map(|s| (s.key.as_str(), s.value.as_str(), s.version))
+                .collect::<Vec<_>>(),
+            vec![("lang", "EN", 1), ("tz", "utc", 2)]
+        );
+
+        // Invalid parameters are reported
+        assert!(Backend::query_raw(
+            &backend,
+            "SELECT * FROM settings WHERE key = :key",
+            vec![("name", EzqlValue::from("tz"))].into(),
+        )
+        .is_err());
+        assert!(Backend::execute_raw(
+            &backend,
+            "DELETE FROM settings WHERE key = ?",
+            RawParams::none(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_statement_cache_sqlite_backend() {
+        let backend = SqliteBackend::new_in_memory();
+        insert_settings(&backend);
+        let before = backend.statement_cache_stats();
+        assert_eq!(before.capacity, DEFAULT_STATEMENT_CACHE_CAPACITY);
+
+        // Same query shape reuses the prepared statement
+        assert_eq!(select_setting(&backend, "theme").value, "dark");
+        assert_eq!(select_setting(&backend, "lang").value, "en");
+        assert_eq!(select_setting(&backend, "tz").value, "utc");
+        let stats = backend.statement_cache_stats();
+        assert_eq!(stats.misses, before.misses + 1);
+        assert_eq!(stats.hits, before.hits + 2);
+        assert_eq!(stats.size, before.size + 1);
+
+        // Least recently used statements are evicted over capacity
+        backend.set_statement_cache_capacity(2);
+        assert_eq!(backend.statement_cache_stats().size, 2);
+        let count = |sql: &str| {
+            Backend::query_raw(&backend, sql, RawParams::none())
+                .unwrap()
+                .len()
+        };
+        count("SELECT 1");
+        count("SELECT 2");
+        count("SELECT 1");
+        count("SELECT 3");
+        let stats = backend.statement_cache_stats();
+        count("SELECT 1");
+        count("SELECT 2");
+        let after = backend.statement_cache_stats();
+        assert_eq!(after.hits, stats.hits + 1);
+        assert_eq!(after.misses, stats.misses + 1);
+        assert_eq!(after.size, 2);
+
+        // Invalid statement does not count or evict cached ones
+        assert!(Backend::query_raw(&backend, "SELEC 1", RawParams::none()).is_err());
+        let failed = backend.statement_cache_stats();
+        assert_eq!(failed.hits, after.hits);
+        assert_eq!(failed.misses, after.misses);
+        assert_eq!(failed.size, 2);
+        count("SELECT 1");
+        count("SELECT 2");
+        assert_eq!(backend.statement_cache_stats().hits, after.hits + 2);
+
+        // Disabled cache always compiles again
+        backend.set_statement_cache_capacity(0);
+        count("SELECT 1");
+        count("SELECT 1");
+        let disabled = backend.statement_cache_stats();
+        assert_eq!(disabled.misses, after.misses + 2);
+        assert_eq!(disabled.hits, after.hits + 2);
+        assert_eq!(disabled.size, 0);
+
+        // Clearing keeps statistics
+        backend.set_statement_cache_capacity(8);
+        count("SELECT 1");
+        backend.clear_statement_cache();
+        let cleared = backend.statement_cache_stats();
+        assert_eq!(cleared.size, 0);
+        assert_eq!(cleared.misses, disabled.misses + 1);
+        count("SELECT 1");
+        assert_eq!(backend.statement_cache_stats().misses, cleared.misses + 1);
+    }
+
+    // ====< Model counting table builds >====
+    static TAG_TABLES: AtomicUsize = AtomicUsize::new(0);
+
+    struct Tag {
+        id: Option<i32>,
+        name: String,
+    }
+
+    impl EzqlModelTrait for Tag {
+        fn get_table() -> Table {
+            TAG_TABLES.fetch_add(1, Ordering::SeqCst);
+            Table {
+                name: "tags".to_string(),
+                columns: vec![
+                    Column::new::<Option<i32>>("id", vec![ColumnProperty::PrimaryKey]),
+                    Column::new::<String>("name", vec![]),
+                ],
+            }
+        }
+
+        fn as_column_values(&self) -> Vec<Option<EzqlValue>> {
+            vec![
+                self.id.as_ref().map(ToEzql::to_ezql),
+                Some(self.name.to_ezql()),
+            ]
+        }
+
+        fn from_column_values(
+            values: Vec<Option<EzqlValue>>,
+        ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(Self {
+                id: decode_column(&values, 0)?,
+                name: decode_column(&values, 1)?,
+            })
+        }
+    }
+
+    #[test]
+    fn test_model_table_cache() {
+        // Overridden table is built once and borrowed
+        let table = Setting::table();
+        assert!(matches!(table, Cow::Borrowed(_)));
+        assert!(std::ptr::eq(&*table, &*Setting::table()));
+        assert_eq!(table.name, Setting::get_table().name);
+        assert!(matches!(User::table(), Cow::Owned(_)));
+
+        // Default table is built once per operation, not per row
+        let backend = SqliteBackend::new_in_memory();
+        backends::ModelBackend::create_table::<Tag>(&backend, false).unwrap();
+        let tags = (0..100)
+            .map(|i| Tag {
+                id: None,
+                name: format!("tag{}", i),
+            })
+            .collect::<Vec<Tag>>();
+        backends::ModelBackend::insert::<Tag>(&backend, &tags.iter().collect::<Vec<&Tag>>())
+            .unwrap();
+        let before = TAG_TABLES.load(Ordering::SeqCst);
+        let tags =
+            backends::ModelBackend::select::<Tag>(&backend, SelectQueryParams::default()).unwrap();
+        assert_eq!(tags.len(), 100);
+        assert_eq!(tags[99].name, "tag99");
+        assert!(tags[0].id.is_some());
+        assert_eq!(TAG_TABLES.load(Ordering::SeqCst), before + 1);
+        let mut stmt = backend
+            .select_iter::<Tag>(SelectQueryParams::default())
+            .unwrap();
+        assert_eq!(stmt.iter().unwrap().filter(Result::is_ok).count(), 100);
+        assert_eq!(TAG_TABLES.load(Ordering::SeqCst), before + 2);
+    }
+
+    #[test]
+    fn test_memoized_sql_sqlite_dialect() {
+        let select = |query: SelectQueryParams| {
+            let table = &Setting::table();
+            let memo =
+                memoized::<SqliteDialect>(Statement::Select, table, Shape::select(&query), || {
+                    SqliteDialect::select(table, query.clone())
+                });
+            let expected = SqliteDialect::select(table, query);
+            assert_eq!(memo.sql, expected.sql);
+            assert_eq!(memo.params, expected.params);
+        };
+
+        // Same shape with other values is served from cache
+        for (key, versions) in [
+            ("theme", vec![1, 2]),
+            ("lang", vec![3, 4]),
+            ("tz", vec![5, 6, 7]),
+        ] {
+            select(SelectQueryParams {
+                columns: Some(vec![
+                    "key".into(),
+                    Projection::ColumnAs("value".into(), "v".into()),
+                ]),
+                where_clause: Some(WhereClause::And(vec![
+                    WhereClause::Eq("key".to_string(), key.into()),
+                    WhereClause::Or(vec![
+                        WhereClause::In(
+                            "version".to_string(),
+                            versions.iter().map(|v| (*v).into()).collect(),
+                        ),
+                        WhereClause::Between("id".to_string(), 1.into(), 9.into()),
+                        WhereClause::Eq("value".to_string(), EzqlValue::Null()),
+                    ]),
+                ])),
+                order_by: vec![OrderBy::desc("version").nulls_last()],
+                limit: Some(versions.len()),
+                ..SelectQueryParams::default()
+            });
+        }
+        select(SelectQueryParams {
+            where_clause: Some(WhereClause::Eq("key".to_string(), EzqlValue::Null())),
+            ..SelectQueryParams::default()
+        });
+        select(SelectQueryParams {
+            where_clause: Some(WhereClause::Eq("key".to_string(), "tz".into())),
+            ..SelectQueryParams::default()
+        });
+
+        let update = |query: UpdateQueryParams| {
+            let table = &Setting::table();
+            let memo = memoized::<SqliteDialect>(
+                Statement::Update,
+                table,
+                Shape::update(table, &query),
+                || SqliteDialect::update(table, query.clone()),
+            );
+            let expected = SqliteDialect::update(table, query);
+            assert_eq!(memo.sql, expected.sql);
+            assert_eq!(memo.params, expected.params);
+        };
+        for value in ["dark", "light"] {
+            update(UpdateQueryParams {
                 set: vec![
-                    ("name".to_string(), "Jane".into()),
-                    ("is_active".to_string(), false.into()),
+                    ("value".to_string(), value.into()),
+                    ("key".to_string(), Expr::column("value")),
                 ],
-                where_clause: Some(WhereClause::All),
+                where_clause: Some(WhereClause::Gt("version".to_string(), 1.into())),
+            });
+        }
+
+        // Shapes with nested params are generated every time
+        assert!(Shape::select(&SelectQueryParams {
+            where_clause: Some(WhereClause::Not(Box::new(WhereClause::IsNull(
+                "key".to_string()
+            )))),
+            ..SelectQueryParams::default()
+        })
+        .is_none());
+        assert!(Shape::select(&SelectQueryParams {
+            joins: vec![Join::cross("users")],
+            ..SelectQueryParams::default()
+        })
+        .is_none());
+        assert!(Shape::update(
+            &Setting::table(),
+            &UpdateQueryParams {
+                set: vec![("version".to_string(), Expr::column("version") + 1)],
+                where_clause: None,
+            }
+        )
+        .is_none());
+        assert!(Shape::select(&SelectQueryParams {
+            offset: Some(10),
+            ..SelectQueryParams::default()
+        })
+        .is_none());
+
+        // Number of memoized statements is bounded
+        for limit in 0..SQL_CACHE_CAPACITY + 10 {
+            select(SelectQueryParams {
+                limit: Some(limit),
+                ..SelectQueryParams::default()
+            });
+        }
+        assert!(memoized_len() <= SQL_CACHE_CAPACITY);
+    }
+
+    // Fresh database file per test, removed with its WAL files
+    struct TempDatabase(String);
+
+    impl TempDatabase {
+        fn new(name: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("ezql_{}_{}.db", name, std::process::id()));
+            let database = Self(path.to_string_lossy().into_owned());
+            database.remove();
+            database
+        }
+
+        fn remove(&self) {
+            for suffix in ["", "-wal", "-shm"] {
+                let _ = std::fs::remove_file(format!("{}{}", self.0, suffix));
+            }
+        }
+    }
+
+    impl Drop for TempDatabase {
+        fn drop(&mut self) {
+            self.remove();
+        }
+    }
+
+    fn wal_pool(database: &TempDatabase, max_size: usize) -> SqlitePool {
+        SqlitePoolOptions::new()
+            .min_size(2)
+            .max_size(max_size)
+            .on_connect(|backend| {
+                let mode =
+                    Backend::query_raw(backend, "PRAGMA journal_mode = WAL;", RawParams::none())?;
+                assert_eq!(mode[0].get::<String>("journal_mode")?, "wal");
+                Ok(())
+            })
+            .open(&database.0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_sqlite_pool_threads() {
+        let database = TempDatabase::new("pool_threads");
+        let pool = wal_pool(&database, 4);
+        assert_eq!((pool.size(), pool.idle()), (2, 2));
+        backends::ModelBackend::create_table::<Setting>(&pool, true).unwrap();
+
+        // Writers and readers share the pool from many threads
+        std::thread::scope(|scope| {
+            for thread in 0..8 {
+                let pool = &pool;
+                scope.spawn(move || {
+                    for i in 0..25 {
+                        let key = format!("key{}_{}", thread, i);
+                        backends::ModelBackend::insert::<Setting>(
+                            pool,
+                            &[&setting(&key, "value", thread)],
+                        )
+                        .unwrap();
+                        let count = backends::ModelBackend::count::<Setting>(
+                            pool,
+                            SelectQueryParams {
+                                where_clause: Some(WhereClause::Eq(
+                                    "version".to_string(),
+                                    thread.into(),
+                                )),
+                                ..SelectQueryParams::default()
+                            },
+                        )
+                        .unwrap();
+                        assert_eq!(count, i as usize + 1);
+                    }
+                });
+            }
+        });
+
+        let total =
+            backends::ModelBackend::count::<Setting>(&pool, SelectQueryParams::default()).unwrap();
+        assert_eq!(total, 200);
+        assert!(pool.size() <= 4);
+        assert_eq!(pool.idle(), pool.size());
+        Backend::close(pool).unwrap();
+    }
+
+    #[test]
+    fn test_sqlite_pool_acquire_timeout() {
+        let database = TempDatabase::new("pool_timeout");
+        let pool = SqlitePoolOptions::new()
+            .min_size(0)
+            .max_size(1)
+            .acquire_timeout(std::time::Duration::from_millis(50))
+            .open(&database.0)
+            .unwrap();
+        assert_eq!(pool.size(), 0);
+
+        let connection = pool.acquire().unwrap();
+        assert_eq!(pool.size(), 1);
+        assert!(pool.acquire().is_err());
+
+        // Released connection is reused
+        drop(connection);
+        assert_eq!(pool.idle(), 1);
+        pool.acquire().unwrap();
+        assert_eq!(pool.size(), 1);
+    }
+
+    #[test]
+    fn test_sqlite_pool_acquire_without_timeout() {
+        let database = TempDatabase::new("pool_no_timeout");
+        let pool = SqlitePoolOptions::new()
+            .min_size(0)
+            .max_size(1)
+            .acquire_timeout(std::time::Duration::MAX)
+            .open(&database.0)
+            .unwrap();
+
+        // Waits until the connection is released
+        let connection = pool.acquire().unwrap();
+        std::thread::scope(|scope| {
+            scope.spawn(move || {
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                drop(connection);
+            });
+            pool.acquire().unwrap();
+        });
+        assert_eq!(pool.size(), 1);
+    }
+
+    #[test]
+    fn test_sqlite_pool_transaction() {
+        let database = TempDatabase::new("pool_transaction");
+        let pool = wal_pool(&database, 2);
+        backends::ModelBackend::create_table::<Setting>(&pool, true).unwrap();
+
+        pool.transaction(|backend| {
+            backends::ModelBackend::insert::<Setting>(backend, &[&setting("theme", "dark", 1)])?;
+            backends::ModelBackend::insert::<Setting>(backend, &[&setting("lang", "en", 1)])?;
+            Ok(())
+        })
+        .unwrap();
+
+        // Failed transaction is rolled back
+        let result: Result<(), _> = pool.transaction(|backend| {
+            backends::ModelBackend::insert::<Setting>(backend, &[&setting("tz", "utc", 2)])?;
+            Err("abort".into())
+        });
+        assert_eq!(result.unwrap_err().to_string(), "abort");
+
+        // Bulk insert and fallbacks nest inside the transaction
+        let table = Setting::get_table();
+        pool.transaction(|backend| {
+            let rows = vec![
+                setting("tz", "utc", 2).as_column_values(),
+                setting("font", "mono", 2).as_column_values(),
+            ];
+            Backend::insert_bulk(backend, &table, rows, &mut |_| {})?;
+            backend.insert_rowids(&table, vec![setting("size", "12", 3).as_column_values()])?;
+            backend.update_returning_fallback(
+                &table,
+                UpdateQueryParams {
+                    set: vec![("version".to_string(), 4.into())],
+                    where_clause: Some(WhereClause::Eq("key".to_string(), "size".into())),
+                },
+            )?;
+            backend.delete_returning_fallback(
+                &table,
+                SelectQueryParams {
+                    where_clause: Some(WhereClause::Eq("version".to_string(), 2.into())),
+                    ..SelectQueryParams::default()
+                },
+            )?;
+
+            // Failed bulk insert only undoes its own rows
+            let rows = vec![
+                setting("color", "red", 5).as_column_values(),
+                setting("size", "14", 5).as_column_values(),
+            ];
+            assert!(Backend::insert_bulk(backend, &table, rows, &mut |_| {}).is_err());
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(select_setting(&pool.acquire().unwrap(), "size").version, 4);
+        backends::ModelBackend::delete::<Setting>(
+            &pool,
+            SelectQueryParams {
+                where_clause: Some(WhereClause::Eq("key".to_string(), "size".into())),
+                ..SelectQueryParams::default()
             },
         )
         .unwrap();
 
-        let users = backends::ModelBackend::select::<User>(
+        // Connection left in transaction is rolled back on release
+        {
+            let connection = pool.acquire().unwrap();
+            Backend::execute_raw(&*connection, "BEGIN;", RawParams::none()).unwrap();
+            backends::ModelBackend::insert::<Setting>(&*connection, &[&setting("tz", "utc", 2)])
+                .unwrap();
+        }
+
+        let keys = backends::ModelBackend::select::<Setting>(
+            &pool,
+            SelectQueryParams {
+                order_by: vec![OrderBy::asc("key")],
+                ..SelectQueryParams::default()
+            },
+        )
+        .unwrap()
+        .into_iter()
+        .map(|s| s.key)
+        .collect::<Vec<String>>();
+        assert_eq!(keys, vec!["lang", "theme"]);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_async_sqlite_backend() {
+        use crate::backends::{
+            async_backend::{AsyncBackend, AsyncModelBackend},
+            sqlite_async::AsyncSqliteBackend,
+        };
+
+        let database = TempDatabase::new("async");
+        let backend = AsyncSqliteBackend::new(wal_pool(&database, 4));
+        AsyncModelBackend::create_table::<Setting>(&backend, true)
+            .await
+            .unwrap();
+
+        // Tasks share the backend, queries run on blocking threads
+        let tasks = (0..8)
+            .map(|task| {
+                let backend = backend.clone();
+                tokio::spawn(async move {
+                    for i in 0..10 {
+                        let key = format!("key{}_{}", task, i);
+                        AsyncModelBackend::insert::<Setting>(
+                            &backend,
+                            &[&setting(&key, "value", task)],
+                        )
+                        .await
+                        .unwrap();
+                    }
+                    AsyncModelBackend::count::<Setting>(
+                        &backend,
+                        SelectQueryParams {
+                            where_clause: Some(WhereClause::Eq("version".to_string(), task.into())),
+                            ..SelectQueryParams::default()
+                        },
+                    )
+                    .await
+                    .unwrap()
+                })
+            })
+            .collect::<Vec<_>>();
+        for task in tasks {
+            assert_eq!(task.await.unwrap(), 10);
+        }
+
+        let inserted = AsyncModelBackend::insert_returning::<Setting>(
+            &backend,
+            &[&setting("theme", "dark", 100)],
+        )
+        .await
+        .unwrap();
+        assert_eq!(inserted[0].key, "theme");
+        assert!(inserted[0].id.is_some());
+
+        let rows = AsyncBackend::query_raw(
+            &backend,
+            "SELECT count(*) AS total FROM settings",
+            RawParams::none(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(rows[0].get::<i32>("total").unwrap(), 81);
+
+        // Failed transaction is rolled back, error message is kept
+        let result = backend
+            .transaction(|backend| {
+                Backend::execute_raw(backend, "DELETE FROM settings", RawParams::none())?;
+                Err::<(), _>("abort".into())
+            })
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "abort");
+        let error = AsyncBackend::execute_raw(&backend, "DELETE FROM missing", RawParams::none())
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("no such table"));
+        assert!(error.downcast_ref::<rusqlite::Error>().is_some());
+
+        // Decode errors keep row and column
+        let error = AsyncModelBackend::query_as::<Setting>(
+            &backend,
+            "SELECT id FROM settings",
+            RawParams::none(),
+        )
+        .await
+        .unwrap_err();
+        let error = error.downcast_ref::<DecodeError>().unwrap();
+        assert_eq!((error.row, error.column.as_deref()), (0, Some("key")));
+
+        let page = AsyncModelBackend::paginate::<Setting>(
             &backend,
             SelectQueryParams {
-                columns: Some(vec!["name".to_string(), "is_active".to_string()]),
-                where_clause: None,
-                order_by: None,
-                limit: None,
-                offset: None,
+                order_by: vec![OrderBy::asc("id")],
+                ..SelectQueryParams::default()
             },
+            2,
+            30,
         )
+        .await
         .unwrap();
-        assert_eq!(users.len(), 1);
-        assert_eq!(users[0].name, Some("Jane".to_string()));
-        assert_eq!(users[0].is_active, Some(false));
+        assert_eq!((page.total, page.items.len()), (81, 30));
+
+        // Closing needs the only handle
+        let clone = backend.clone();
+        assert!(AsyncBackend::close(clone).await.is_err());
+        AsyncBackend::close(backend).await.unwrap();
     }
 }
@@ -0,0 +1,591 @@
+use std::{
+    ops::Deref,
+    sync::{Arc, Condvar, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    dialects::SqliteDialect,
+    prelude::{EzqlModelTrait, EzqlRecordTrait, EzqlValue, Row, Table},
+    queries::{
+        CompoundSelect, Keyset, KeysetPage, OnConflict, Page, RawParams, SelectQueryParams,
+        UpdateQueryParams,
+    },
+};
+
+use super::{sqlite_backend::SqliteBackend, Backend, BulkInsertProgress, ModelBackend};
+
+// Hook run once on every new connection
+type ConnectHook = Arc<
+    dyn Fn(&SqliteBackend) -> Result<(), Box<dyn std::error::Error + Send + Sync>> + Send + Sync,
+>;
+
+// ====< SQLite pool options >====
+#[derive(Clone)]
+pub struct SqlitePoolOptions {
+    min_size: usize,
+    max_size: usize,
+    acquire_timeout: Duration,
+    busy_timeout: Duration,
+    health_check: bool,
+    on_connect: Option<ConnectHook>,
+}
+
+impl Default for SqlitePoolOptions {
+    fn default() -> Self {
+        Self {
+            min_size: 1,
+            max_size: 8,
+            acquire_timeout: Duration::from_secs(30),
+            busy_timeout: Duration::from_secs(5),
+            health_check: true,
+            on_connect: None,
+        }
+    }
+}
+
+impl SqlitePoolOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Connections opened eagerly
+    pub fn min_size(mut self, size: usize) -> Self {
+        self.min_size = size;
+        self
+    }
+    pub fn max_size(mut self, size: usize) -> Self {
+        self.max_size = size.max(1);
+        self
+    }
+    pub fn acquire_timeout(mut self, timeout: Duration) -> Self {
+        self.acquire_timeout = timeout;
+        self
+    }
+    // How long a connection waits for locks held by other connections
+    pub fn busy_timeout(mut self, timeout: Duration) -> Self {
+        self.busy_timeout = timeout;
+        self
+    }
+    // Check idle connections before handing them out
+    pub fn health_check(mut self, enabled: bool) -> Self {
+        self.health_check = enabled;
+        self
+    }
+    pub fn on_connect<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&SqliteBackend) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.on_connect = Some(Arc::new(hook));
+        self
+    }
+
+    // ====< Open pool on database file >====
+    pub fn open(self, path: &str) -> Result<SqlitePool, Box<dyn std::error::Error + Send + Sync>> {
+        let pool = SqlitePool {
+            path: path.to_string(),
+            state: Mutex::new(PoolState {
+                idle: Vec::new(),
+                open: 0,
+            }),
+            available: Condvar::new(),
+            options: self,
+        };
+
+        let min_size = pool.options.min_size.min(pool.options.max_size);
+        let mut idle = Vec::with_capacity(min_size);
+        for _ in 0..min_size {
+            idle.push(pool.connect()?);
+        }
+        let mut state = pool.state.lock().unwrap();
+        state.open = idle.len();
+        state.idle = idle;
+        drop(state);
+        Ok(pool)
+    }
+}
+
+// ====< SQLite connection pool >====
+// Every connection opens the same file, in-memory databases are not shared
+pub struct SqlitePool {
+    path: String,
+    options: SqlitePoolOptions,
+    state: Mutex<PoolState>,
+    available: Condvar,
+}
+
+struct PoolState {
+    idle: Vec<SqliteBackend>,
+    // Idle and checked out connections
+    open: usize,
+}
+
+impl SqlitePool {
+    pub fn new(path: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        SqlitePoolOptions::new().open(path)
+    }
+
+    // ====< Open and initialize new connection >====
+    fn connect(&self) -> Result<SqliteBackend, Box<dyn std::error::Error + Send + Sync>> {
+        let backend = SqliteBackend::open(&self.path)?;
+        backend.set_busy_timeout(self.options.busy_timeout)?;
+        if let Some(hook) = &self.options.on_connect {
+            hook(&backend)?;
+        }
+        Ok(backend)
+    }
+
+    // ====< Acquire connection, waits until one is free >====
+    pub fn acquire(
+        &self,
+    ) -> Result<PooledConnection<'_>, Box<dyn std::error::Error + Send + Sync>> {
+        // Timeout too large for a deadline waits forever
+        let deadline = Instant::now().checked_add(self.options.acquire_timeout);
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(backend) = state.idle.pop() {
+                drop(state);
+                if !self.options.health_check || backend.is_healthy() {
+                    return Ok(PooledConnection::new(self, backend));
+                }
+                // Broken connection frees its slot
+                drop(backend);
+                self.discard();
+                state = self.state.lock().unwrap();
+                continue;
+            }
+
+            if state.open < self.options.max_size {
+                state.open += 1;
+                drop(state);
+                return match self.connect() {
+                    Ok(backend) => Ok(PooledConnection::new(self, backend)),
+                    Err(e) => {
+                        self.discard();
+                        Err(e)
+                    }
+                };
+            }
+
+            let Some(deadline) = deadline else {
+                state = self.available.wait(state).unwrap();
+                continue;
+            };
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(format!(
+                    "Timed out after {:?} waiting for connection",
+                    self.options.acquire_timeout
+                )
+                .into());
+            }
+            state = self
+                .available
+                .wait_timeout(state, deadline - now)
+                .unwrap()
+                .0;
+        }
+    }
+
+    // ====< Run closure in transaction on single connection >====
+    // Rolled back when the connection returns to the pool if the closure fails
+    pub fn transaction<T, F>(&self, f: F) -> Result<T, Box<dyn std::error::Error + Send + Sync>>
+    where
+        F: FnOnce(&SqliteBackend) -> Result<T, Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let backend = self.acquire()?;
+        // Take write lock up front, upgrading a read lock can not wait on busy timeout
+        Backend::execute_raw(&*backend, "BEGIN IMMEDIATE;", RawParams::none())?;
+        let value = f(&backend)?;
+        Backend::execute_raw(&*backend, "COMMIT;", RawParams::none())?;
+        Ok(value)
+    }
+
+    // Open connections, idle or checked out
+    pub fn size(&self) -> usize {
+        self.state.lock().unwrap().open
+    }
+    pub fn idle(&self) -> usize {
+        self.state.lock().unwrap().idle.len()
+    }
+
+    fn discard(&self) {
+        self.state.lock().unwrap().open -= 1;
+        self.available.notify_one();
+    }
+
+    fn release(&self, backend: SqliteBackend) {
+        self.state.lock().unwrap().idle.push(backend);
+        self.available.notify_one();
+    }
+}
+
+// ====< Connection checked out of pool >====
+// Returned to the pool when dropped
+pub struct PooledConnection<'pool> {
+    pool: &'pool SqlitePool,
+    backend: Option<SqliteBackend>,
+}
+
+impl<'pool> PooledConnection<'pool> {
+    fn new(pool: &'pool SqlitePool, backend: SqliteBackend) -> Self {
+        Self {
+            pool,
+            backend: Some(backend),
+        }
+    }
+}
+
+impl Deref for PooledConnection<'_> {
+    type Target = SqliteBackend;
+
+    fn deref(&self) -> &SqliteBackend {
+        self.backend.as_ref().unwrap()
+    }
+}
+
+impl Drop for PooledConnection<'_> {
+    fn drop(&mut self) {
+        if let Some(backend) = self.backend.take() {
+            // Never hand out a connection with an open transaction
+            if !backend.is_autocommit()
+                && Backend::execute_raw(&backend, "ROLLBACK;", RawParams::none()).is_err()
+            {
+                self.pool.discard();
+                return;
+            }
+            self.pool.release(backend);
+        }
+    }
+}
+
+// ====< SQLite pool trait implementation, one connection per operation >====
+impl Backend<SqliteDialect> for SqlitePool {
+    // ====< Close all connections >====
+    fn close(self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let idle = std::mem::take(&mut self.state.lock().unwrap().idle);
+        for backend in idle {
+            backend.close()?;
+        }
+        Ok(())
+    }
+
+    fn create_table(
+        &self,
+        if_not_exists: bool,
+        table: Table,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Backend::create_table(&*self.acquire()?, if_not_exists, table)
+    }
+
+    fn drop_table(
+        &self,
+        if_exists: bool,
+        table: Table,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Backend::drop_table(&*self.acquire()?, if_exists, table)
+    }
+
+    fn introspect_table(
+        &self,
+        name: &str,
+    ) -> Result<Table, Box<dyn std::error::Error + Send + Sync>> {
+        self.acquire()?.introspect_table(name)
+    }
+
+    fn insert(
+        &self,
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+    ) -> Result<Vec<EzqlValue>, Box<dyn std::error::Error + Send + Sync>> {
+        Backend::insert(&*self.acquire()?, table, models)
+    }
+
+    fn insert_returning(
+        &self,
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+    ) -> Result<Vec<Vec<Option<EzqlValue>>>, Box<dyn std::error::Error + Send + Sync>> {
+        Backend::insert_returning(&*self.acquire()?, table, models)
+    }
+
+    fn insert_bulk(
+        &self,
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+        on_progress: &mut dyn FnMut(BulkInsertProgress),
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        Backend::insert_bulk(&*self.acquire()?, table, models, on_progress)
+    }
+
+    fn upsert(
+        &self,
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+        conflict: Vec<String>,
+        on_conflict: OnConflict,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        Backend::upsert(&*self.acquire()?, table, models, conflict, on_conflict)
+    }
+
+    fn select(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> Result<Vec<Row>, Box<dyn std::error::Error + Send + Sync>> {
+        Backend::select(&*self.acquire()?, table, query)
+    }
+
+    fn select_values(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> Result<Vec<Vec<Option<EzqlValue>>>, Box<dyn std::error::Error + Send + Sync>> {
+        self.acquire()?.select_values(table, query)
+    }
+
+    fn select_as<R>(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> Result<Vec<R>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        R: EzqlRecordTrait,
+    {
+        self.acquire()?.select_as(table, query)
+    }
+
+    fn select_compound(
+        &self,
+        compound: CompoundSelect,
+    ) -> Result<Vec<Vec<Option<EzqlValue>>>, Box<dyn std::error::Error + Send + Sync>> {
+        Backend::select_compound(&*self.acquire()?, compound)
+    }
+
+    fn count(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        Backend::count(&*self.acquire()?, table, query)
+    }
+
+    fn delete(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        Backend::delete(&*self.acquire()?, table, query)
+    }
+
+    fn delete_returning(
+        &self,
+        table: &Table,
+        query: SelectQueryParams,
+    ) -> Result<Vec<Vec<Option<EzqlValue>>>, Box<dyn std::error::Error + Send + Sync>> {
+        Backend::delete_returning(&*self.acquire()?, table, query)
+    }
+
+    fn update(
+        &self,
+        table: &Table,
+        query: UpdateQueryParams,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        Backend::update(&*self.acquire()?, table, query)
+    }
+
+    fn update_returning(
+        &self,
+        table: &Table,
+        query: UpdateQueryParams,
+    ) -> Result<Vec<Vec<Option<EzqlValue>>>, Box<dyn std::error::Error + Send + Sync>> {
+        Backend::update_returning(&*self.acquire()?, table, query)
+    }
+
+    fn execute_raw(
+        &self,
+        sql: &str,
+        params: RawParams,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        self.acquire()?.execute_raw(sql, params)
+    }
+
+    fn query_raw(
+        &self,
+        sql: &str,
+        params: RawParams,
+    ) -> Result<Vec<Row>, Box<dyn std::error::Error + Send + Sync>> {
+        self.acquire()?.query_raw(sql, params)
+    }
+}
+
+// ====< SQLite pool model backend trait implementation >====
+impl ModelBackend<SqliteDialect> for SqlitePool {
+    fn create_table<M>(
+        &self,
+        if_not_exists: bool,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        ModelBackend::create_table::<M>(&*self.acquire()?, if_not_exists)
+    }
+
+    fn drop_table<M>(&self, if_exists: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        ModelBackend::drop_table::<M>(&*self.acquire()?, if_exists)
+    }
+
+    fn insert<M>(
+        &self,
+        models: &[&M],
+    ) -> Result<Vec<EzqlValue>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        ModelBackend::insert(&*self.acquire()?, models)
+    }
+
+    fn insert_returning<M>(
+        &self,
+        models: &[&M],
+    ) -> Result<Vec<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        ModelBackend::insert_returning(&*self.acquire()?, models)
+    }
+
+    fn insert_bulk<M>(
+        &self,
+        models: &[&M],
+        on_progress: &mut dyn FnMut(BulkInsertProgress),
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        ModelBackend::insert_bulk(&*self.acquire()?, models, on_progress)
+    }
+
+    fn upsert<M>(
+        &self,
+        models: &[&M],
+        conflict: Vec<String>,
+        on_conflict: OnConflict,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        ModelBackend::upsert(&*self.acquire()?, models, conflict, on_conflict)
+    }
+
+    fn select<M>(
+        &self,
+        query: SelectQueryParams,
+    ) -> Result<Vec<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        ModelBackend::select(&*self.acquire()?, query)
+    }
+
+    fn select_compound<M>(
+        &self,
+        compound: CompoundSelect,
+    ) -> Result<Vec<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        ModelBackend::select_compound(&*self.acquire()?, compound)
+    }
+
+    fn count<M>(
+        &self,
+        query: SelectQueryParams,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        ModelBackend::count::<M>(&*self.acquire()?, query)
+    }
+
+    // Count and page are read on the same connection
+    fn paginate<M>(
+        &self,
+        query: SelectQueryParams,
+        page: usize,
+        per_page: usize,
+    ) -> Result<Page<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        ModelBackend::paginate(&*self.acquire()?, query, page, per_page)
+    }
+
+    fn paginate_keyset<M>(
+        &self,
+        query: SelectQueryParams,
+        keyset: Keyset,
+    ) -> Result<KeysetPage<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        ModelBackend::paginate_keyset(&*self.acquire()?, query, keyset)
+    }
+
+    fn delete<M>(
+        &self,
+        query: SelectQueryParams,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        ModelBackend::delete::<M>(&*self.acquire()?, query)
+    }
+
+    fn delete_returning<M>(
+        &self,
+        query: SelectQueryParams,
+    ) -> Result<Vec<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        ModelBackend::delete_returning(&*self.acquire()?, query)
+    }
+
+    fn update<M>(
+        &self,
+        query: UpdateQueryParams,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        ModelBackend::update::<M>(&*self.acquire()?, query)
+    }
+
+    fn update_returning<M>(
+        &self,
+        query: UpdateQueryParams,
+    ) -> Result<Vec<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        ModelBackend::update_returning(&*self.acquire()?, query)
+    }
+
+    fn query_as<M>(
+        &self,
+        sql: &str,
+        params: RawParams,
+    ) -> Result<Vec<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        ModelBackend::query_as(&*self.acquire()?, sql, params)
+    }
+}
@@ -0,0 +1,125 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use crate::{
+    components::{model::DecodeError, row::RowColumn},
+    prelude::{EzqlModelTrait, EzqlValue, Table},
+};
+
+use super::sqlite_backend::{read_row, row_columns};
+
+// ====< Prepared select statement >====
+pub struct SelectStatement<'conn, M> {
+    statement: rusqlite::CachedStatement<'conn>,
+    params: Vec<EzqlValue>,
+    table: Table,
+    columns: Arc<[RowColumn]>,
+    model: PhantomData<M>,
+}
+
+impl<'conn, M> SelectStatement<'conn, M>
+where
+    M: EzqlModelTrait,
+{
+    pub(super) fn new(
+        statement: rusqlite::CachedStatement<'conn>,
+        params: Vec<EzqlValue>,
+        table: Table,
+    ) -> Self {
+        let columns = row_columns(&statement);
+        Self {
+            statement,
+            params,
+            table,
+            columns,
+            model: PhantomData,
+        }
+    }
+
+    // ====< Iterate over models one row at a time >====
+    pub fn iter(&mut self) -> Result<SelectIter<'_, M>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows = self
+            .statement
+            .query(rusqlite::params_from_iter(self.params.as_slice()))?;
+        Ok(SelectIter {
+            rows,
+            table: &self.table,
+            columns: &self.columns,
+            row: 0,
+            model: PhantomData,
+        })
+    }
+
+    // ====< Iterate over batches of models >====
+    pub fn batches(
+        &mut self,
+        size: usize,
+    ) -> Result<SelectBatches<'_, M>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(SelectBatches {
+            iter: self.iter()?,
+            size: size.max(1),
+        })
+    }
+}
+
+// ====< Streaming model iterator >====
+pub struct SelectIter<'stmt, M> {
+    rows: rusqlite::Rows<'stmt>,
+    table: &'stmt Table,
+    columns: &'stmt Arc<[RowColumn]>,
+    row: usize,
+    model: PhantomData<M>,
+}
+
+impl<M> Iterator for SelectIter<'_, M>
+where
+    M: EzqlModelTrait,
+{
+    type Item = Result<M, Box<dyn std::error::Error + Send + Sync>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let values = match self.rows.next() {
+            Ok(Some(row)) => read_row(row, self.columns),
+            Ok(None) => return None,
+            Err(e) => return Some(Err(e.into())),
+        };
+
+        // Decode model
+        let row = self.row;
+        self.row += 1;
+        Some(match values {
+            Ok(values) => M::from_row(&values, self.table)
+                .map_err(|e| DecodeError::new(self.table, row, e).into()),
+            Err(e) => Err(e.into()),
+        })
+    }
+}
+
+// ====< Batched model cursor >====
+pub struct SelectBatches<'stmt, M> {
+    iter: SelectIter<'stmt, M>,
+    size: usize,
+}
+
+impl<M> Iterator for SelectBatches<'_, M>
+where
+    M: EzqlModelTrait,
+{
+    type Item = Result<Vec<M>, Box<dyn std::error::Error + Send + Sync>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Batch size comes from caller, do not preallocate all of it
+        let mut batch = Vec::with_capacity(self.size.min(1024));
+        for model in self.iter.by_ref().take(self.size) {
+            match model {
+                Ok(model) => batch.push(model),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+
+        if batch.is_empty() {
+            None
+        } else {
+            Some(Ok(batch))
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{prelude::EzqlValue, types::EzqlType};
+use crate::{
+    prelude::EzqlValue,
+    types::{EzqlType, ToEzql},
+};
 
 // ====< SQL column >====
 #[derive(Debug, Clone)]
@@ -9,26 +12,65 @@ pub struct Column {
 }
 
 // ====< SQL column property >====
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ColumnProperty {
     PrimaryKey,
     NotNull,
     Unique,
     Default(EzqlValue),
+    Check(String),
+    Generated { expr: String, stored: bool },
 }
 
-// create default column property
+// create column properties
 impl ColumnProperty {
     pub fn default<T>(value: T) -> Self
     where
-        T: Into<EzqlValue>,
+        T: ToEzql,
     {
-        ColumnProperty::Default(value.into())
+        ColumnProperty::Default(value.to_ezql())
+    }
+
+    pub fn check(expr: &str) -> Self {
+        ColumnProperty::Check(expr.to_string())
+    }
+
+    pub fn generated(expr: &str, stored: bool) -> Self {
+        ColumnProperty::Generated {
+            expr: expr.to_string(),
+            stored,
+        }
     }
 }
 
 // ====< Impl column >====
 impl Column {
+    // Create column from rust type, non-optional types imply NOT NULL
+    pub fn new<T>(name: &str, mut properties: Vec<ColumnProperty>) -> Self
+    where
+        T: ToEzql,
+    {
+        if !T::nullable()
+            && !properties
+                .iter()
+                .any(|p| matches!(p, ColumnProperty::NotNull))
+        {
+            properties.push(ColumnProperty::NotNull);
+        }
+        Self {
+            name: name.to_string(),
+            data_type: T::ezql_type(),
+            properties,
+        }
+    }
+
+    pub fn is_nullable(&self) -> bool {
+        !self
+            .properties
+            .iter()
+            .any(|p| matches!(p, ColumnProperty::NotNull | ColumnProperty::PrimaryKey))
+    }
+
     pub fn has_default(&self) -> bool {
         self.properties
             .iter()
@@ -47,6 +89,12 @@ impl Column {
             .iter()
             .any(|p| matches!(p, ColumnProperty::PrimaryKey))
     }
+
+    pub fn is_generated(&self) -> bool {
+        self.properties
+            .iter()
+            .any(|p| matches!(p, ColumnProperty::Generated { .. }))
+    }
 }
 
 // ====< Pretty print column name >====
@@ -0,0 +1,240 @@
+use crate::{
+    components::query::{OrderBy, WhereClause},
+    prelude::{EzqlType, EzqlValue},
+    types::ToEzql,
+};
+
+// ====< SQL expression >====
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Column(String),
+    Value(EzqlValue),
+    Binary(Box<Expr>, BinaryOp, Box<Expr>),
+    Function(String, Vec<Expr>),
+    Cast(Box<Expr>, EzqlType),
+    // CASE WHEN ... THEN ... ELSE ... END
+    Case(Vec<(WhereClause, Expr)>, Option<Box<Expr>>),
+    // Function applied over window, f(...) OVER (...)
+    Window(Box<Expr>, Window),
+}
+
+// ====< Binary operator >====
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Concat,
+}
+
+impl Expr {
+    pub fn column(name: impl Into<String>) -> Self {
+        Expr::Column(name.into())
+    }
+    pub fn value(value: impl Into<EzqlValue>) -> Self {
+        Expr::Value(value.into())
+    }
+    pub fn function(name: impl Into<String>, args: Vec<Expr>) -> Self {
+        Expr::Function(name.into(), args)
+    }
+
+    // ====< Common functions >====
+    pub fn lower(self) -> Self {
+        Expr::function("lower", vec![self])
+    }
+    pub fn upper(self) -> Self {
+        Expr::function("upper", vec![self])
+    }
+    pub fn length(self) -> Self {
+        Expr::function("length", vec![self])
+    }
+    pub fn abs(self) -> Self {
+        Expr::function("abs", vec![self])
+    }
+    pub fn coalesce(exprs: Vec<Expr>) -> Self {
+        Expr::function("coalesce", exprs)
+    }
+    pub fn cast(self, data_type: EzqlType) -> Self {
+        Expr::Cast(Box::new(self), data_type)
+    }
+    pub fn concat(self, other: impl Into<Expr>) -> Self {
+        Expr::Binary(Box::new(self), BinaryOp::Concat, Box::new(other.into()))
+    }
+
+    // ====< Aggregate and window functions >====
+    pub fn sum(self) -> Self {
+        Expr::function("sum", vec![self])
+    }
+    pub fn avg(self) -> Self {
+        Expr::function("avg", vec![self])
+    }
+    pub fn min(self) -> Self {
+        Expr::function("min", vec![self])
+    }
+    pub fn max(self) -> Self {
+        Expr::function("max", vec![self])
+    }
+    pub fn count(self) -> Self {
+        Expr::function("count", vec![self])
+    }
+    pub fn count_all() -> Self {
+        Expr::function("count", vec![Expr::column("*")])
+    }
+    pub fn row_number() -> Self {
+        Expr::function("row_number", vec![])
+    }
+    pub fn rank() -> Self {
+        Expr::function("rank", vec![])
+    }
+    pub fn dense_rank() -> Self {
+        Expr::function("dense_rank", vec![])
+    }
+    pub fn lag(self, offset: i32) -> Self {
+        Expr::function("lag", vec![self, offset.into()])
+    }
+    pub fn lead(self, offset: i32) -> Self {
+        Expr::function("lead", vec![self, offset.into()])
+    }
+    pub fn over(self, window: Window) -> Self {
+        Expr::Window(Box::new(self), window)
+    }
+
+    // ====< CASE WHEN builder >====
+    pub fn case() -> CaseBuilder {
+        CaseBuilder::default()
+    }
+}
+
+// ====< CASE WHEN builder, finished with otherwise or end >====
+#[derive(Debug, Clone, Default)]
+pub struct CaseBuilder {
+    branches: Vec<(WhereClause, Expr)>,
+}
+
+impl CaseBuilder {
+    pub fn when(mut self, condition: WhereClause, then: impl Into<Expr>) -> Self {
+        self.branches.push((condition, then.into()));
+        self
+    }
+    pub fn otherwise(self, value: impl Into<Expr>) -> Expr {
+        Expr::Case(self.branches, Some(Box::new(value.into())))
+    }
+    // Without ELSE unmatched rows are NULL
+    pub fn end(self) -> Expr {
+        Expr::Case(self.branches, None)
+    }
+}
+
+impl From<CaseBuilder> for Expr {
+    fn from(case: CaseBuilder) -> Self {
+        case.end()
+    }
+}
+
+// ====< Window definition >====
+#[derive(Debug, Clone, Default)]
+pub struct Window {
+    pub partition_by: Vec<Expr>,
+    pub order_by: Vec<OrderBy>,
+    pub frame: Option<Frame>,
+}
+
+impl Window {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn partition_by(mut self, expr: Expr) -> Self {
+        self.partition_by.push(expr);
+        self
+    }
+    pub fn order_by(mut self, order_by: OrderBy) -> Self {
+        self.order_by.push(order_by);
+        self
+    }
+    pub fn rows(mut self, start: FrameBound, end: FrameBound) -> Self {
+        self.frame = Some(Frame::new(FrameUnits::Rows, start, end));
+        self
+    }
+    pub fn range(mut self, start: FrameBound, end: FrameBound) -> Self {
+        self.frame = Some(Frame::new(FrameUnits::Range, start, end));
+        self
+    }
+    pub fn groups(mut self, start: FrameBound, end: FrameBound) -> Self {
+        self.frame = Some(Frame::new(FrameUnits::Groups, start, end));
+        self
+    }
+}
+
+// ====< Window frame >====
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame {
+    pub units: FrameUnits,
+    pub start: FrameBound,
+    pub end: FrameBound,
+}
+
+impl Frame {
+    pub fn new(units: FrameUnits, start: FrameBound, end: FrameBound) -> Self {
+        Self { units, start, end }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameUnits {
+    Rows,
+    Range,
+    Groups,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameBound {
+    UnboundedPreceding,
+    Preceding(usize),
+    CurrentRow,
+    Following(usize),
+    UnboundedFollowing,
+}
+
+// ====< Conversions >====
+impl<T> From<T> for Expr
+where
+    T: ToEzql,
+{
+    fn from(value: T) -> Self {
+        Expr::Value(value.to_ezql())
+    }
+}
+
+impl From<EzqlValue> for Expr {
+    fn from(value: EzqlValue) -> Self {
+        Expr::Value(value)
+    }
+}
+
+// ====< Arithmetic operators >====
+macro_rules! impl_binary_ops {
+    ($($trait:ident::$method:ident => $op:ident),*,) => {
+        $(
+            impl<T> std::ops::$trait<T> for Expr
+            where
+                T: Into<Expr>,
+            {
+                type Output = Expr;
+
+                fn $method(self, other: T) -> Expr {
+                    Expr::Binary(Box::new(self), BinaryOp::$op, Box::new(other.into()))
+                }
+            }
+        )*
+    };
+}
+
+impl_binary_ops!(
+    Add::add => Add,
+    Sub::sub => Sub,
+    Mul::mul => Mul,
+    Div::div => Div,
+    Rem::rem => Mod,
+);
@@ -1,4 +1,9 @@
 pub mod column;
+pub mod expr;
 pub mod model;
+pub mod pagination;
 pub mod query;
+pub mod row;
+#[cfg(feature = "sqlite")]
+pub(crate) mod sql_cache;
 pub mod table;
@@ -1,4 +1,9 @@
-use crate::prelude::{EzqlValue, Table};
+use std::borrow::Cow;
+
+use crate::{
+    prelude::{EzqlValue, Row, Table},
+    types::{ConversionError, FromEzql},
+};
 
 // ====< Trait for models >====
 pub trait EzqlModelTrait {
@@ -6,7 +11,183 @@ pub trait EzqlModelTrait {
     fn as_column_values(&self) -> Vec<Option<EzqlValue>>;
     fn from_column_values(
         values: Vec<Option<EzqlValue>>,
-    ) -> Result<Self, Box<dyn std::error::Error>>
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>>
     where
         Self: Sized;
+
+    // Table used by backends, built once per operation by default,
+    // override to borrow it from a static OnceLock built only once
+    fn table() -> Cow<'static, Table>
+    where
+        Self: Sized,
+    {
+        Cow::Owned(Self::get_table())
+    }
+
+    // Columns are matched by name, so projection order does not matter,
+    // table is passed in so it is not rebuilt for every row
+    fn from_row(row: &Row, table: &Table) -> Result<Self, Box<dyn std::error::Error + Send + Sync>>
+    where
+        Self: Sized,
+    {
+        Self::from_column_values(row.values_for(table))
+    }
+}
+
+// ====< Trait for ad-hoc records, decoded in projection order >====
+pub trait EzqlRecordTrait {
+    fn from_values(
+        values: Vec<Option<EzqlValue>>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>>
+    where
+        Self: Sized;
+}
+
+// ====< Tuples of convertible values are records >====
+macro_rules! impl_ezql_record_tuples {
+    ($(($($T:ident => $idx:tt),*)),*,) => {
+        $(
+            impl<$($T),*> EzqlRecordTrait for ($($T,)*)
+            where
+                $($T: FromEzql),*
+            {
+                fn from_values(
+                    values: Vec<Option<EzqlValue>>,
+                ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+                    Ok(($(decode_column::<$T>(&values, $idx)?,)*))
+                }
+            }
+        )*
+    };
+}
+
+impl_ezql_record_tuples!(
+    (A => 0),
+    (A => 0, B => 1),
+    (A => 0, B => 1, C => 2),
+    (A => 0, B => 1, C => 2, D => 3),
+    (A => 0, B => 1, C => 2, D => 3, E => 4),
+    (A => 0, B => 1, C => 2, D => 3, E => 4, F => 5),
+);
+
+// ====< Decode single column value >====
+// Missing values are decoded as NULL, so only Option<T> accepts them
+pub fn decode_column<T>(values: &[Option<EzqlValue>], index: usize) -> Result<T, ConversionError>
+where
+    T: FromEzql,
+{
+    let value = values
+        .get(index)
+        .cloned()
+        .flatten()
+        .unwrap_or(EzqlValue::Null());
+    T::from_ezql(value).map_err(|e| e.at_column(index))
+}
+
+// ====< Decode rows into models >====
+#[cfg(feature = "sqlite")]
+pub(crate) fn decode_models<M>(
+    table: &Table,
+    rows: Vec<Vec<Option<EzqlValue>>>,
+) -> Result<Vec<M>, Box<dyn std::error::Error + Send + Sync>>
+where
+    M: EzqlModelTrait,
+{
+    let mut result = Vec::new();
+    for (row, values) in rows.into_iter().enumerate() {
+        result.push(M::from_column_values(values).map_err(|e| DecodeError::new(table, row, e))?);
+    }
+    Ok(result)
+}
+
+// ====< Decode named rows into models >====
+#[cfg(feature = "sqlite")]
+pub(crate) fn decode_model_rows<M>(
+    table: &Table,
+    rows: Vec<Row>,
+) -> Result<Vec<M>, Box<dyn std::error::Error + Send + Sync>>
+where
+    M: EzqlModelTrait,
+{
+    let mut result = Vec::new();
+    for (index, row) in rows.iter().enumerate() {
+        result.push(M::from_row(row, table).map_err(|e| DecodeError::new(table, index, e))?);
+    }
+    Ok(result)
+}
+
+// ====< Decode rows into records >====
+#[cfg(feature = "sqlite")]
+pub(crate) fn decode_records<R>(
+    columns: &[String],
+    rows: Vec<Vec<Option<EzqlValue>>>,
+) -> Result<Vec<R>, Box<dyn std::error::Error + Send + Sync>>
+where
+    R: EzqlRecordTrait,
+{
+    let mut result = Vec::new();
+    for (row, values) in rows.into_iter().enumerate() {
+        result.push(R::from_values(values).map_err(|source| {
+            // Columns are in projection order
+            let column = source
+                .downcast_ref::<ConversionError>()
+                .and_then(|e| e.column)
+                .and_then(|i| columns.get(i))
+                .cloned();
+            DecodeError {
+                row,
+                column,
+                source,
+            }
+        })?);
+    }
+    Ok(result)
+}
+
+// ====< Model decoding error >====
+#[derive(Debug)]
+pub struct DecodeError {
+    pub row: usize,
+    pub column: Option<String>,
+    pub source: Box<dyn std::error::Error + Send + Sync>,
+}
+
+impl DecodeError {
+    pub fn new(
+        table: &Table,
+        row: usize,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    ) -> Self {
+        // Resolve column name if the error comes from a column conversion
+        let column = source
+            .downcast_ref::<ConversionError>()
+            .and_then(|e| e.column)
+            .and_then(|i| table.columns.get(i))
+            .map(|c| c.name.clone());
+        Self {
+            row,
+            column,
+            source,
+        }
+    }
+}
+
+// ====< Pretty print decode error >====
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.column {
+            Some(column) => write!(
+                f,
+                "Failed to decode row {} (column {}): {}",
+                self.row, column, self.source
+            ),
+            None => write!(f, "Failed to decode row {}: {}", self.row, self.source),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.source.as_ref())
+    }
 }
@@ -0,0 +1,284 @@
+#[cfg(feature = "sqlite")]
+use crate::{
+    components::model::decode_model_rows,
+    prelude::{EzqlModelTrait, Row, Table},
+    queries::{Direction, SelectQueryParams, WhereClause},
+};
+use crate::{
+    prelude::EzqlValue,
+    queries::OrderBy,
+    utils::{from_hex, to_hex},
+};
+
+// ====< Keyset cursor >====
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cursor {
+    After(Vec<EzqlValue>),
+    Before(Vec<EzqlValue>),
+}
+
+impl Cursor {
+    pub fn values(&self) -> &[EzqlValue] {
+        match self {
+            Cursor::After(values) | Cursor::Before(values) => values,
+        }
+    }
+
+    // ====< Encode cursor as opaque token >====
+    pub fn encode(&self) -> String {
+        let direction = match self {
+            Cursor::After(_) => "a",
+            Cursor::Before(_) => "b",
+        };
+        let values = self
+            .values()
+            .iter()
+            .map(|value| match value {
+                EzqlValue::Integer(v) => format!("i{}", v),
+                EzqlValue::VarChar(v) => format!("s{}", to_hex(v.as_bytes())),
+                EzqlValue::Enum(v) => format!("e{}", to_hex(v.as_bytes())),
+                EzqlValue::Boolean(v) => format!("b{}", *v as u8),
+                EzqlValue::Null() => "n".to_string(),
+            })
+            .collect::<Vec<String>>()
+            .join(".");
+        to_hex(format!("{}:{}", direction, values).as_bytes())
+    }
+
+    // ====< Decode cursor from token >====
+    pub fn decode(token: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let invalid = || format!("Invalid cursor {}", token);
+        let decoded = from_hex(token)
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(invalid)?;
+        let (direction, values) = decoded.split_once(':').ok_or_else(invalid)?;
+
+        // Decode values
+        let text = |hex: &str| from_hex(hex).and_then(|bytes| String::from_utf8(bytes).ok());
+        let values = values
+            .split('.')
+            .filter(|value| !value.is_empty())
+            .map(|value| {
+                if let Some(v) = value.strip_prefix('i') {
+                    v.parse().ok().map(EzqlValue::Integer)
+                } else if let Some(v) = value.strip_prefix('s') {
+                    text(v).map(EzqlValue::VarChar)
+                } else if let Some(v) = value.strip_prefix('e') {
+                    text(v).map(EzqlValue::Enum)
+                } else if let Some(v) = value.strip_prefix('b') {
+                    Some(EzqlValue::Boolean(v == "1"))
+                } else if value == "n" {
+                    Some(EzqlValue::Null())
+                } else {
+                    None
+                }
+            })
+            .collect::<Option<Vec<EzqlValue>>>()
+            .ok_or_else(invalid)?;
+
+        match direction {
+            "a" => Ok(Cursor::After(values)),
+            "b" => Ok(Cursor::Before(values)),
+            _ => Err(invalid().into()),
+        }
+    }
+}
+
+// ====< Keyset pagination parameters >====
+#[derive(Debug, Clone)]
+pub struct Keyset {
+    // Ordering on columns that uniquely identify a row
+    pub order_by: Vec<OrderBy>,
+    pub cursor: Option<String>,
+    pub per_page: usize,
+}
+
+#[cfg(feature = "sqlite")]
+impl Keyset {
+    // ====< Add seek predicate, ordering and limit to select query >====
+    pub(crate) fn apply(
+        &self,
+        mut query: SelectQueryParams,
+    ) -> Result<(SelectQueryParams, Option<Cursor>), Box<dyn std::error::Error + Send + Sync>> {
+        if self.per_page == 0 {
+            return Err("per_page must be greater than 0".into());
+        }
+        // Fetch one more row to know if there is another page
+        let limit = self
+            .per_page
+            .checked_add(1)
+            .ok_or_else(|| format!("per_page {} is out of range", self.per_page))?;
+        let cursor = self.cursor.as_deref().map(Cursor::decode).transpose()?;
+        let backward = matches!(cursor, Some(Cursor::Before(_)));
+
+        // Seek past the cursor
+        if let Some(cursor) = &cursor {
+            if cursor.values().len() != self.order_by.len() {
+                return Err("Cursor does not match keyset ordering".into());
+            }
+            // Comparison with NULL matches nothing and would end pagination
+            if cursor.values().contains(&EzqlValue::Null()) {
+                return Err("Cursor contains NULL key".into());
+            }
+            let seek = Self::seek_clause(&self.order_by, cursor.values(), backward);
+            query.where_clause = Some(match query.where_clause.take() {
+                Some(where_clause) => WhereClause::And(vec![where_clause, seek]),
+                None => seek,
+            });
+        }
+
+        // Walk backwards in reversed order
+        query.order_by = if backward {
+            self.order_by.iter().map(OrderBy::reversed).collect()
+        } else {
+            self.order_by.clone()
+        };
+
+        query.limit = Some(limit);
+        query.offset = None;
+
+        // Keys are needed for cursors
+        if let Some(columns) = query.columns.as_mut() {
+            for order_by in self.order_by.iter() {
+                if !columns.iter().any(|c| {
+                    c.name() == order_by.column() || c.name() == unqualified(order_by.column())
+                }) {
+                    columns.push(order_by.column().into());
+                }
+            }
+        }
+
+        Ok((query, cursor))
+    }
+
+    // ====< Build page from fetched rows >====
+    pub(crate) fn page<M>(
+        &self,
+        table: &Table,
+        cursor: Option<Cursor>,
+        mut rows: Vec<Row>,
+    ) -> Result<KeysetPage<M>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        M: EzqlModelTrait,
+    {
+        let backward = matches!(cursor, Some(Cursor::Before(_)));
+        let has_more = rows.len() > self.per_page;
+        rows.truncate(self.per_page);
+        if backward {
+            rows.reverse();
+        }
+
+        // Read keys of first and last row by column name,
+        // NULL keys can not be sought past so they are rejected
+        let keys = |row: &Row| {
+            self.order_by
+                .iter()
+                .map(|o| {
+                    let index = row
+                        .index_of(o.column())
+                        .or_else(|| row.index_of(unqualified(o.column())))
+                        .ok_or_else(|| format!("Unknown keyset column {}", o.column()))?;
+                    row.values()[index].clone().ok_or_else(|| {
+                        format!(
+                            "Keyset column {} is NULL, keys must be NOT NULL",
+                            o.column()
+                        )
+                    })
+                })
+                .collect::<Result<Vec<EzqlValue>, String>>()
+        };
+
+        // There is a next page if more rows were found walking forward,
+        // or if walking backward from a cursor, and vice versa
+        let next_cursor = rows
+            .last()
+            .filter(|_| if backward { cursor.is_some() } else { has_more })
+            .map(|row| keys(row).map(|keys| Cursor::After(keys).encode()))
+            .transpose()?;
+        let prev_cursor = rows
+            .first()
+            .filter(|_| if backward { has_more } else { cursor.is_some() })
+            .map(|row| keys(row).map(|keys| Cursor::Before(keys).encode()))
+            .transpose()?;
+
+        Ok(KeysetPage {
+            items: decode_model_rows(table, rows)?,
+            next_cursor,
+            prev_cursor,
+        })
+    }
+
+    // ====< Predicate selecting rows past the key values >====
+    fn seek_clause(order_by: &[OrderBy], values: &[EzqlValue], backward: bool) -> WhereClause {
+        let ascending = |o: &OrderBy| (o.direction == Direction::Asc) != backward;
+        let compare = |o: &OrderBy, value: &EzqlValue| {
+            if ascending(o) {
+                WhereClause::Gt(o.column().to_string(), value.clone())
+            } else {
+                WhereClause::Lt(o.column().to_string(), value.clone())
+            }
+        };
+
+        // Single column needs simple comparison
+        if order_by.len() == 1 {
+            return compare(&order_by[0], &values[0]);
+        }
+
+        // Same direction for all columns allows row value comparison
+        let columns = order_by.iter().map(|o| o.column().to_string()).collect();
+        if order_by.iter().all(ascending) {
+            return WhereClause::RowGt(columns, values.to_vec());
+        }
+        if order_by.iter().all(|o| !ascending(o)) {
+            return WhereClause::RowLt(columns, values.to_vec());
+        }
+
+        // Mixed directions, (a > ?) OR (a = ? AND b < ?) ...
+        WhereClause::Or(
+            (0..order_by.len())
+                .map(|i| {
+                    let mut clauses = (0..i)
+                        .map(|j| {
+                            WhereClause::Eq(order_by[j].column().to_string(), values[j].clone())
+                        })
+                        .collect::<Vec<WhereClause>>();
+                    clauses.push(compare(&order_by[i], &values[i]));
+                    WhereClause::And(clauses)
+                })
+                .collect(),
+        )
+    }
+}
+
+// ====< Result columns are named without table qualifier >====
+#[cfg(feature = "sqlite")]
+fn unqualified(column: &str) -> &str {
+    column
+        .rsplit('.')
+        .next()
+        .unwrap_or(column)
+        .trim_matches(|c| c == '"' || c == '`')
+}
+
+// ====< Keyset page >====
+#[derive(Debug, Clone)]
+pub struct KeysetPage<M> {
+    pub items: Vec<M>,
+    pub next_cursor: Option<String>,
+    pub prev_cursor: Option<String>,
+}
+
+// ====< Offset page >====
+#[derive(Debug, Clone)]
+pub struct Page<M> {
+    pub items: Vec<M>,
+    pub total: usize,
+    pub page: usize,
+    pub per_page: usize,
+}
+
+impl<M> Page<M> {
+    pub fn total_pages(&self) -> usize {
+        self.total.div_ceil(self.per_page.max(1))
+    }
+}
@@ -1,4 +1,7 @@
-use crate::prelude::EzqlValue;
+use crate::{
+    components::expr::Expr,
+    prelude::{EzqlModelTrait, EzqlValue, Table},
+};
 
 // ====< SQL query >====
 #[derive(Debug, Clone)]
@@ -27,13 +30,97 @@ impl std::fmt::Display for Query {
 }
 
 // ====< Select query parameters >====
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct SelectQueryParams {
-    pub columns: Option<Vec<String>>,
+    pub columns: Option<Vec<Projection>>,
     pub where_clause: Option<WhereClause>,
-    pub order_by: Option<OrderBy>,
+    pub order_by: Vec<OrderBy>,
     pub limit: Option<usize>,
     pub offset: Option<usize>,
+    pub joins: Vec<Join>,
+    // Common table expressions, queryable like tables
+    pub with: Vec<Cte>,
+    pub distinct: Option<Distinct>,
+}
+
+// ====< Distinct rows >====
+#[derive(Debug, Clone)]
+pub enum Distinct {
+    // SELECT DISTINCT
+    Rows,
+    // First row of each group, in query order
+    On(Vec<Expr>),
+}
+
+// ====< Join kind >====
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinKind {
+    Inner,
+    Left,
+    Cross,
+}
+
+// ====< Joined table >====
+#[derive(Debug, Clone)]
+pub struct Join {
+    pub kind: JoinKind,
+    pub table: String,
+    pub on: Option<WhereClause>,
+}
+
+impl Join {
+    pub fn inner(table: impl Into<String>, on: WhereClause) -> Self {
+        Self {
+            kind: JoinKind::Inner,
+            table: table.into(),
+            on: Some(on),
+        }
+    }
+    pub fn left(table: impl Into<String>, on: WhereClause) -> Self {
+        Self {
+            kind: JoinKind::Left,
+            table: table.into(),
+            on: Some(on),
+        }
+    }
+    pub fn cross(table: impl Into<String>) -> Self {
+        Self {
+            kind: JoinKind::Cross,
+            table: table.into(),
+            on: None,
+        }
+    }
+}
+
+// ====< Common table expression >====
+#[derive(Debug, Clone)]
+pub struct Cte {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub query: Subquery,
+    // Recursive member, combined with UNION ALL and may reference the CTE itself
+    pub recursive: Option<Subquery>,
+}
+
+impl Cte {
+    pub fn new(name: impl Into<String>, query: Subquery) -> Self {
+        Self {
+            name: name.into(),
+            columns: vec![],
+            query,
+            recursive: None,
+        }
+    }
+    pub fn recursive(name: impl Into<String>, anchor: Subquery, recursive: Subquery) -> Self {
+        Self {
+            recursive: Some(recursive),
+            ..Self::new(name, anchor)
+        }
+    }
+    pub fn columns(mut self, columns: Vec<&str>) -> Self {
+        self.columns = columns.into_iter().map(str::to_string).collect();
+        self
+    }
 }
 
 // ====< Where clause >====
@@ -54,18 +141,288 @@ pub enum WhereClause {
     IsNotNull(String),
     In(String, Vec<EzqlValue>),
     NotIn(String, Vec<EzqlValue>),
+    // Row value comparisons, (a, b) > (?, ?)
+    RowGt(Vec<String>, Vec<EzqlValue>),
+    RowLt(Vec<String>, Vec<EzqlValue>),
+    Between(String, EzqlValue, EzqlValue),
+    // Case insensitive LIKE
+    ILike(String, EzqlValue),
+    Glob(String, EzqlValue),
+    Regexp(String, EzqlValue),
+    Exists(Box<Subquery>),
+    // Column to column comparison, a = b
+    CompareColumns(String, CompareOp, String),
+    // Subquery comparisons, a IN (SELECT ...)
+    InSubquery(String, Box<Subquery>),
+    NotInSubquery(String, Box<Subquery>),
+    CompareSubquery(String, CompareOp, Box<Subquery>),
+    // Expression comparison, lower(email) = ?
+    Compare(Expr, CompareOp, Expr),
+}
+
+// ====< Selected column >====
+#[derive(Debug, Clone)]
+pub enum Projection {
+    Column(String),
+    // Column with alias
+    ColumnAs(String, String),
+    // Scalar subquery with alias
+    Subquery(Box<Subquery>, String),
+    // Expression with alias
+    Expr(Expr, String),
+}
+
+impl Projection {
+    // Name of the result column
+    pub fn name(&self) -> &str {
+        match self {
+            Projection::Column(name)
+            | Projection::ColumnAs(_, name)
+            | Projection::Subquery(_, name)
+            | Projection::Expr(_, name) => name,
+        }
+    }
+}
+
+impl Projection {
+    pub fn alias(column: impl Into<String>, alias: impl Into<String>) -> Self {
+        Projection::ColumnAs(column.into(), alias.into())
+    }
+}
+
+impl From<&str> for Projection {
+    fn from(column: &str) -> Self {
+        Projection::Column(column.to_string())
+    }
+}
+
+impl From<String> for Projection {
+    fn from(column: String) -> Self {
+        Projection::Column(column)
+    }
+}
+
+// ====< Compound select operator >====
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompoundOp {
+    Union,
+    UnionAll,
+    Intersect,
+    Except,
 }
 
-// ====< Order by >====
+// ====< Selects combined with UNION, INTERSECT or EXCEPT >====
 #[derive(Debug, Clone)]
-pub enum OrderBy {
-    Asc(String),
-    Desc(String),
+pub struct CompoundSelect {
+    pub first: Subquery,
+    pub rest: Vec<(CompoundOp, Subquery)>,
+    // Applied to the combined result
+    pub order_by: Vec<OrderBy>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+impl CompoundSelect {
+    pub fn new(first: Subquery) -> Self {
+        Self {
+            first,
+            rest: vec![],
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        }
+    }
+    pub fn union(self, other: Subquery) -> Self {
+        self.combine(CompoundOp::Union, other)
+    }
+    pub fn union_all(self, other: Subquery) -> Self {
+        self.combine(CompoundOp::UnionAll, other)
+    }
+    pub fn intersect(self, other: Subquery) -> Self {
+        self.combine(CompoundOp::Intersect, other)
+    }
+    pub fn except(self, other: Subquery) -> Self {
+        self.combine(CompoundOp::Except, other)
+    }
+    pub fn combine(mut self, op: CompoundOp, other: Subquery) -> Self {
+        self.rest.push((op, other));
+        self
+    }
+
+    // Names of result columns, taken from first select
+    pub fn column_names(&self) -> Vec<String> {
+        match &self.first.query.columns {
+            Some(columns) => columns.iter().map(|c| c.name().to_string()).collect(),
+            None => self
+                .first
+                .table
+                .columns
+                .iter()
+                .map(|c| c.name.clone())
+                .collect(),
+        }
+    }
+}
+
+// ====< Comparison operator >====
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+// ====< Select used inside another query >====
+#[derive(Debug, Clone)]
+pub struct Subquery {
+    pub table: Table,
+    pub query: SelectQueryParams,
+}
+
+impl Subquery {
+    pub fn new(table: Table, query: SelectQueryParams) -> Self {
+        Self { table, query }
+    }
+    pub fn model<M>(query: SelectQueryParams) -> Self
+    where
+        M: EzqlModelTrait,
+    {
+        Self::new(M::get_table(), query)
+    }
+}
+
+// ====< Sort direction >====
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Asc,
+    Desc,
+}
+
+// ====< Placement of NULL values >====
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Nulls {
+    First,
+    Last,
+}
+
+// ====< Order by term >====
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderBy {
+    // Column name or SQL expression
+    pub expr: String,
+    pub direction: Direction,
+    pub nulls: Option<Nulls>,
+    pub collate: Option<String>,
+}
+
+impl OrderBy {
+    pub fn new(expr: impl Into<String>, direction: Direction) -> Self {
+        Self {
+            expr: expr.into(),
+            direction,
+            nulls: None,
+            collate: None,
+        }
+    }
+    pub fn asc(expr: impl Into<String>) -> Self {
+        Self::new(expr, Direction::Asc)
+    }
+    pub fn desc(expr: impl Into<String>) -> Self {
+        Self::new(expr, Direction::Desc)
+    }
+
+    pub fn nulls_first(mut self) -> Self {
+        self.nulls = Some(Nulls::First);
+        self
+    }
+    pub fn nulls_last(mut self) -> Self {
+        self.nulls = Some(Nulls::Last);
+        self
+    }
+    pub fn collate(mut self, collation: impl Into<String>) -> Self {
+        self.collate = Some(collation.into());
+        self
+    }
+
+    pub fn column(&self) -> &str {
+        &self.expr
+    }
+
+    // ====< Opposite ordering, including NULL placement >====
+    pub fn reversed(&self) -> Self {
+        Self {
+            direction: match self.direction {
+                Direction::Asc => Direction::Desc,
+                Direction::Desc => Direction::Asc,
+            },
+            nulls: self.nulls.map(|nulls| match nulls {
+                Nulls::First => Nulls::Last,
+                Nulls::Last => Nulls::First,
+            }),
+            ..self.clone()
+        }
+    }
+}
+
+// ====< Parameters of raw SQL >====
+#[derive(Debug, Clone, PartialEq)]
+pub enum RawParams {
+    // Bound to ?, ?1, ...
+    Positional(Vec<EzqlValue>),
+    // Bound to :name, names are stored with prefix
+    Named(Vec<(String, EzqlValue)>),
+}
+
+impl RawParams {
+    pub fn none() -> Self {
+        RawParams::Positional(vec![])
+    }
+
+    pub fn named(params: Vec<(&str, EzqlValue)>) -> Self {
+        RawParams::Named(
+            params
+                .into_iter()
+                .map(|(name, value)| {
+                    // Accept names with or without prefix
+                    let name = if name.starts_with([':', '@', '$']) {
+                        name.to_string()
+                    } else {
+                        format!(":{}", name)
+                    };
+                    (name, value)
+                })
+                .collect(),
+        )
+    }
+}
+
+impl From<Vec<EzqlValue>> for RawParams {
+    fn from(params: Vec<EzqlValue>) -> Self {
+        RawParams::Positional(params)
+    }
+}
+
+impl From<Vec<(&str, EzqlValue)>> for RawParams {
+    fn from(params: Vec<(&str, EzqlValue)>) -> Self {
+        RawParams::named(params)
+    }
 }
 
 // ====< Update query parameters >====
 #[derive(Debug, Clone)]
 pub struct UpdateQueryParams {
-    pub set: Vec<(String, EzqlValue)>,
+    pub set: Vec<(String, Expr)>,
     pub where_clause: Option<WhereClause>,
 }
+
+// ====< Upsert conflict policy >====
+#[derive(Debug, Clone)]
+pub enum OnConflict {
+    DoNothing,
+    UpdateAll,
+    Update(Vec<String>),
+    Set(UpdateQueryParams),
+}
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use crate::{
+    prelude::{EzqlType, EzqlValue, Table},
+    types::FromEzql,
+};
+
+// ====< Result column metadata >====
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowColumn {
+    pub name: String,
+    // Declared type, only known for plain table columns
+    pub data_type: Option<EzqlType>,
+}
+
+impl RowColumn {
+    pub fn new(name: impl Into<String>, data_type: Option<EzqlType>) -> Self {
+        Self {
+            name: name.into(),
+            data_type,
+        }
+    }
+
+    // Columns without type information
+    pub fn untyped(names: &[String]) -> Arc<[RowColumn]> {
+        names
+            .iter()
+            .map(|name| RowColumn::new(name.as_str(), None))
+            .collect()
+    }
+}
+
+// ====< Result row with named columns >====
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    // Shared by all rows of one result
+    columns: Arc<[RowColumn]>,
+    values: Vec<Option<EzqlValue>>,
+}
+
+impl Row {
+    pub fn new(columns: Arc<[RowColumn]>, values: Vec<Option<EzqlValue>>) -> Self {
+        Self { columns, values }
+    }
+
+    pub fn columns(&self) -> &[RowColumn] {
+        &self.columns
+    }
+    pub fn column_names(&self) -> Vec<&str> {
+        self.columns.iter().map(|c| c.name.as_str()).collect()
+    }
+    pub fn values(&self) -> &[Option<EzqlValue>] {
+        &self.values
+    }
+    pub fn into_values(self) -> Vec<Option<EzqlValue>> {
+        self.values
+    }
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    // ====< Position of column, first match wins >====
+    pub fn index_of(&self, column: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c.name == column)
+    }
+
+    // ====< Raw value, None for NULL >====
+    pub fn value(&self, column: &str) -> Option<&EzqlValue> {
+        self.index_of(column).and_then(|i| self.values[i].as_ref())
+    }
+
+    // ====< Values in table column order, missing columns are None >====
+    pub fn values_for(&self, table: &Table) -> Vec<Option<EzqlValue>> {
+        table
+            .columns
+            .iter()
+            .map(|c| self.value(&c.name).cloned())
+            .collect()
+    }
+
+    // ====< Decode value by column name >====
+    pub fn get<T>(&self, column: &str) -> Result<T, Box<dyn std::error::Error + Send + Sync>>
+    where
+        T: FromEzql,
+    {
+        let index = self
+            .index_of(column)
+            .ok_or_else(|| format!("No column named {}", column))?;
+        self.get_index(index)
+    }
+
+    // ====< Decode value by position >====
+    pub fn get_index<T>(&self, index: usize) -> Result<T, Box<dyn std::error::Error + Send + Sync>>
+    where
+        T: FromEzql,
+    {
+        let value = self
+            .values
+            .get(index)
+            .ok_or_else(|| format!("No column at index {}", index))?
+            .clone()
+            .unwrap_or(EzqlValue::Null());
+        Ok(T::from_ezql(value).map_err(|e| e.at_column(index))?)
+    }
+}
+
+// ====< Index by position or column name >====
+impl std::ops::Index<usize> for Row {
+    type Output = Option<EzqlValue>;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.values[index]
+    }
+}
+
+impl std::ops::Index<&str> for Row {
+    type Output = Option<EzqlValue>;
+
+    fn index(&self, column: &str) -> &Self::Output {
+        match self.index_of(column) {
+            Some(index) => &self.values[index],
+            None => panic!("No column named {}", column),
+        }
+    }
+}
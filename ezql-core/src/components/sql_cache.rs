@@ -0,0 +1,295 @@
+use std::{
+    any::TypeId,
+    collections::{HashMap, VecDeque},
+    sync::{Arc, OnceLock, RwLock},
+};
+
+use crate::{
+    components::{
+        expr::Expr,
+        query::{CompareOp, Direction, Distinct, Nulls, Projection, Query},
+    },
+    prelude::{EzqlValue, Table},
+    queries::{SelectQueryParams, UpdateQueryParams, WhereClause},
+};
+
+// ====< Memoized statement kind >====
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Statement {
+    Select,
+    Count,
+    Delete,
+    Update,
+}
+
+// Generated SQL only depends on table name and query shape,
+// columns excluded from update SET are part of the shape
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct SqlKey {
+    dialect: TypeId,
+    table: String,
+    statement: Statement,
+    shape: String,
+}
+
+// ====< Maximum number of memoized statements >====
+pub(crate) const SQL_CACHE_CAPACITY: usize = 1024;
+
+// ====< Generated SQL, oldest entries are evicted over capacity >====
+#[derive(Default)]
+struct SqlCache {
+    sql: HashMap<SqlKey, Arc<str>>,
+    order: VecDeque<SqlKey>,
+}
+
+impl SqlCache {
+    fn insert(&mut self, key: SqlKey, sql: Arc<str>) {
+        // Another thread may have generated it meanwhile
+        if self.sql.contains_key(&key) {
+            return;
+        }
+        while self.order.len() >= SQL_CACHE_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.sql.remove(&oldest);
+            }
+        }
+        self.order.push_back(key.clone());
+        self.sql.insert(key, sql);
+    }
+}
+
+static SQL: OnceLock<RwLock<SqlCache>> = OnceLock::new();
+
+// ====< Query with parameter values left out >====
+// Only shapes whose SQL does not depend on values are supported,
+// params are collected in the order the dialects bind them
+#[derive(Debug, Default)]
+pub(crate) struct Shape {
+    key: String,
+    params: Vec<EzqlValue>,
+}
+
+impl Shape {
+    pub(crate) fn select(query: &SelectQueryParams) -> Option<Self> {
+        // Joins, CTEs and DISTINCT ON contribute their own params
+        if !query.joins.is_empty() || !query.with.is_empty() {
+            return None;
+        }
+        let mut shape = Shape::default();
+        match &query.distinct {
+            None => {}
+            Some(Distinct::Rows) => shape.tag("distinct"),
+            Some(Distinct::On(_)) => return None,
+        }
+
+        if let Some(columns) = &query.columns {
+            shape.tag("columns");
+            for column in columns {
+                match column {
+                    Projection::Column(name) => shape.name(name),
+                    Projection::ColumnAs(name, alias) => {
+                        shape.name(name);
+                        shape.tag("as");
+                        shape.name(alias);
+                    }
+                    _ => return None,
+                }
+            }
+        }
+
+        if let Some(where_clause) = &query.where_clause {
+            shape.tag("where");
+            shape.where_clause(where_clause)?;
+        }
+
+        for order_by in &query.order_by {
+            shape.tag("order");
+            shape.name(&order_by.expr);
+            shape.tag(match order_by.direction {
+                Direction::Asc => "asc",
+                Direction::Desc => "desc",
+            });
+            match order_by.nulls {
+                Some(Nulls::First) => shape.tag("first"),
+                Some(Nulls::Last) => shape.tag("last"),
+                None => {}
+            }
+            if let Some(collation) = &order_by.collate {
+                shape.tag("collate");
+                shape.name(collation);
+            }
+        }
+
+        // Limit is written into SQL, offset changes on every page
+        // so it would fill the cache with single use statements
+        if query.offset.is_some() {
+            return None;
+        }
+        if let Some(limit) = query.limit {
+            shape.tag("limit");
+            shape.name(&limit.to_string());
+        }
+        Some(shape)
+    }
+
+    pub(crate) fn update(table: &Table, query: &UpdateQueryParams) -> Option<Self> {
+        let mut shape = Shape::default();
+        for (column, expr) in &query.set {
+            // Generated columns are skipped by dialects
+            if table
+                .columns
+                .iter()
+                .any(|c| c.name == *column && c.is_generated())
+            {
+                continue;
+            }
+            shape.tag("set");
+            shape.name(column);
+            match expr {
+                Expr::Column(name) => shape.name(name),
+                Expr::Value(value) => shape.param(value),
+                _ => return None,
+            }
+        }
+
+        if let Some(where_clause) = &query.where_clause {
+            shape.tag("where");
+            shape.where_clause(where_clause)?;
+        }
+        Some(shape)
+    }
+
+    fn where_clause(&mut self, where_clause: &WhereClause) -> Option<()> {
+        match where_clause {
+            WhereClause::All => self.tag("all"),
+            WhereClause::And(clauses) | WhereClause::Or(clauses) => {
+                self.tag(match where_clause {
+                    WhereClause::And(_) => "and",
+                    _ => "or",
+                });
+                for clause in clauses {
+                    self.where_clause(clause)?;
+                }
+                self.tag("end");
+            }
+            // Comparing with NULL is written without param
+            WhereClause::Eq(column, EzqlValue::Null()) => self.column("eq_null", column),
+            WhereClause::Ne(column, EzqlValue::Null()) => self.column("ne_null", column),
+            WhereClause::Eq(column, value) => self.compare("eq", column, value),
+            WhereClause::Ne(column, value) => self.compare("ne", column, value),
+            WhereClause::Gt(column, value) => self.compare("gt", column, value),
+            WhereClause::Ge(column, value) => self.compare("ge", column, value),
+            WhereClause::Lt(column, value) => self.compare("lt", column, value),
+            WhereClause::Le(column, value) => self.compare("le", column, value),
+            WhereClause::Like(column, value) => self.compare("like", column, value),
+            WhereClause::ILike(column, value) => self.compare("ilike", column, value),
+            WhereClause::Glob(column, value) => self.compare("glob", column, value),
+            WhereClause::Regexp(column, value) => self.compare("regexp", column, value),
+            WhereClause::IsNull(column) => self.column("is_null", column),
+            WhereClause::IsNotNull(column) => self.column("is_not_null", column),
+            WhereClause::In(column, values) | WhereClause::NotIn(column, values) => {
+                self.column(
+                    match where_clause {
+                        WhereClause::In(..) => "in",
+                        _ => "not_in",
+                    },
+                    column,
+                );
+                values.iter().for_each(|v| self.param(v));
+                self.tag("end");
+            }
+            WhereClause::RowGt(columns, values) | WhereClause::RowLt(columns, values) => {
+                self.tag(match where_clause {
+                    WhereClause::RowGt(..) => "row_gt",
+                    _ => "row_lt",
+                });
+                columns.iter().for_each(|c| self.name(c));
+                values.iter().for_each(|v| self.param(v));
+                self.tag("end");
+            }
+            WhereClause::Between(column, low, high) => {
+                self.compare("between", column, low);
+                self.param(high);
+            }
+            WhereClause::CompareColumns(left, op, right) => {
+                self.column(compare_op(*op), left);
+                self.name(right);
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+
+    fn tag(&mut self, tag: &str) {
+        self.key.push_str(tag);
+        self.key.push('\0');
+    }
+    // Identifiers can not contain NUL, so it separates parts unambiguously
+    fn name(&mut self, name: &str) {
+        self.key.push_str(name);
+        self.key.push('\0');
+    }
+    fn param(&mut self, value: &EzqlValue) {
+        self.tag("?");
+        self.params.push(value.clone());
+    }
+    fn column(&mut self, tag: &str, column: &str) {
+        self.tag(tag);
+        self.name(column);
+    }
+    fn compare(&mut self, tag: &str, column: &str, value: &EzqlValue) {
+        self.column(tag, column);
+        self.param(value);
+    }
+}
+
+fn compare_op(op: CompareOp) -> &'static str {
+    match op {
+        CompareOp::Eq => "col_eq",
+        CompareOp::Ne => "col_ne",
+        CompareOp::Gt => "col_gt",
+        CompareOp::Ge => "col_ge",
+        CompareOp::Lt => "col_lt",
+        CompareOp::Le => "col_le",
+    }
+}
+
+// ====< SQL for query shape, generated once per dialect and table >====
+pub(crate) fn memoized<D>(
+    statement: Statement,
+    table: &Table,
+    shape: Option<Shape>,
+    generate: impl FnOnce() -> Query,
+) -> Query
+where
+    D: 'static,
+{
+    let Some(shape) = shape else {
+        return generate();
+    };
+    let key = SqlKey {
+        dialect: TypeId::of::<D>(),
+        table: table.name.clone(),
+        statement,
+        shape: shape.key,
+    };
+
+    let cache = SQL.get_or_init(Default::default);
+    if let Some(sql) = cache.read().unwrap().sql.get(&key) {
+        return Query::new(sql.to_string(), shape.params);
+    }
+
+    let query = generate();
+    debug_assert_eq!(query.params, shape.params, "shape params out of order");
+    cache
+        .write()
+        .unwrap()
+        .insert(key, Arc::from(query.sql.as_str()));
+    query
+}
+
+// ====< Number of memoized statements >====
+#[cfg(test)]
+pub(crate) fn memoized_len() -> usize {
+    SQL.get().map_or(0, |cache| cache.read().unwrap().sql.len())
+}
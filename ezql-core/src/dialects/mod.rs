@@ -1,7 +1,11 @@
 use crate::{
     components::{
         column::ColumnProperty,
-        query::{OrderBy, Query, SelectQueryParams, UpdateQueryParams, WhereClause},
+        expr::Expr,
+        query::{
+            CompoundSelect, OnConflict, OrderBy, Projection, Query, SelectQueryParams,
+            UpdateQueryParams, WhereClause,
+        },
         table::Table,
     },
     prelude::EzqlValue,
@@ -14,6 +18,9 @@ pub mod sqlite_dialect;
 pub use sqlite_dialect::SqliteDialect;
 
 pub trait Dialect {
+    // ====< Limits >====
+    fn max_params() -> usize;
+
     // ====< Basic translations >====
     fn translate_type(t: EzqlType) -> String;
     fn translate_value(v: EzqlValue) -> String;
@@ -22,12 +29,23 @@ pub trait Dialect {
 
     // ====< Query translations >====
     fn translate_where_clause(where_clause: WhereClause) -> Query;
+    fn translate_projection(projection: Projection) -> Query;
+    fn translate_expr(expr: Expr) -> Query;
 
     // ====< Advanced translations >====
     fn create_table(if_not_exists: bool, table: Table) -> Query;
     fn drop_table(if_exists: bool, table: Table) -> Query;
     fn insert(table: &Table, models: Vec<Vec<Option<EzqlValue>>>) -> Query;
+    fn upsert(
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+        conflict: Vec<String>,
+        on_conflict: OnConflict,
+    ) -> Query;
+    fn returning(query: Query, columns: Option<Vec<String>>) -> Query;
     fn select(table: &Table, query_params: SelectQueryParams) -> Query;
+    fn count(table: &Table, query_params: SelectQueryParams) -> Query;
+    fn compound_select(compound: CompoundSelect) -> Query;
     fn delete(table: &Table, query_params: SelectQueryParams) -> Query;
     fn update(table: &Table, query_params: UpdateQueryParams) -> Query;
 }
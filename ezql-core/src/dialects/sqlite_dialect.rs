@@ -1,8 +1,14 @@
 use crate::components::column::ColumnProperty::Default;
-use crate::components::query::{OrderBy, Query, SelectQueryParams, UpdateQueryParams, WhereClause};
+use crate::components::expr::{BinaryOp, Expr, FrameBound, FrameUnits, Window};
+use crate::components::query::{
+    CompareOp, CompoundOp, CompoundSelect, Cte, Direction, Distinct, Join, JoinKind, Nulls,
+    OnConflict, OrderBy, Projection, Query, SelectQueryParams, Subquery, UpdateQueryParams,
+    WhereClause,
+};
 use crate::components::table::Table;
 use crate::dialects::Dialect;
 use crate::types::{EzqlType, EzqlValue};
+use crate::utils::{parenthesized, split_top_level};
 
 // ====< Dialect for SQLite >====
 #[derive(Default)]
@@ -15,6 +21,8 @@ impl Dialect for SqliteDialect {
             EzqlType::Integer() => "INTEGER".to_string(),
             EzqlType::VarChar(len) => format!("VARCHAR({})", len),
             EzqlType::Boolean() => "BOOLEAN".to_string(),
+            // Allowed values are checked by constraint added in create_table
+            EzqlType::Enum { .. } => "TEXT".to_string(),
             #[allow(unreachable_patterns)]
             _ => unimplemented!("Type {:?} is not implemented for SQLite dialect", t),
         }
@@ -24,8 +32,11 @@ impl Dialect for SqliteDialect {
     fn translate_value(v: EzqlValue) -> String {
         match v {
             EzqlValue::Integer(value) => value.to_string(),
-            EzqlValue::VarChar(value) => format!("'{}'", value),
+            EzqlValue::VarChar(value) | EzqlValue::Enum(value) => {
+                format!("'{}'", value.replace('\'', "''"))
+            }
             EzqlValue::Boolean(value) => value.to_string().to_ascii_uppercase(),
+            EzqlValue::Null() => "NULL".to_string(),
             #[allow(unreachable_patterns)]
             _ => unimplemented!("Value {:?} is not implemented for SQLite dialect", v),
         }
@@ -40,6 +51,14 @@ impl Dialect for SqliteDialect {
             Default(value) => {
                 format!("DEFAULT {}", Self::translate_value(value))
             }
+            crate::components::column::ColumnProperty::Check(expr) => {
+                format!("CHECK ({})", expr)
+            }
+            crate::components::column::ColumnProperty::Generated { expr, stored } => format!(
+                "GENERATED ALWAYS AS ({}) {}",
+                expr,
+                if stored { "STORED" } else { "VIRTUAL" }
+            ),
             #[allow(unreachable_patterns)]
             _ => unimplemented!("Property {:?} is not implemented for SQLite dialect", p),
         }
@@ -73,6 +92,13 @@ impl Dialect for SqliteDialect {
                     }
                 }
             }
+            // Comparing with NULL is never true, use IS (NOT) NULL instead
+            WhereClause::Eq(column, EzqlValue::Null()) => {
+                query.sql = format!("{} IS NULL", column);
+            }
+            WhereClause::Ne(column, EzqlValue::Null()) => {
+                query.sql = format!("{} IS NOT NULL", column);
+            }
             WhereClause::Eq(column, value) => {
                 query.params.push(value);
                 query.sql = format!("{} = ?", column);
@@ -102,7 +128,9 @@ impl Dialect for SqliteDialect {
                 query.sql = format!("{} LIKE ?", column);
             }
             WhereClause::Not(clause) => {
-                query.sql = format!("NOT {}", Self::translate_where_clause(*clause));
+                let clause = Self::translate_where_clause(*clause);
+                query.params.extend(clause.params);
+                query.sql = format!("NOT ({})", clause.sql);
             }
             WhereClause::IsNull(column) => {
                 query.sql = format!("{} IS NULL", column);
@@ -112,11 +140,87 @@ impl Dialect for SqliteDialect {
             }
             WhereClause::In(column, values) => {
                 query.params.extend(values.clone());
-                query.sql = format!("{} IN ({})", column, "?,".repeat(values.len() - 1));
+                query.sql = format!("{} IN ({})", column, vec!["?"; values.len()].join(", "));
             }
             WhereClause::NotIn(column, values) => {
                 query.params.extend(values.clone());
-                query.sql = format!("{} NOT IN ({})", column, "?,".repeat(values.len() - 1));
+                query.sql = format!("{} NOT IN ({})", column, vec!["?"; values.len()].join(", "));
+            }
+
+            WhereClause::RowGt(columns, values) => {
+                query.sql = format!(
+                    "({}) > ({})",
+                    columns.join(", "),
+                    vec!["?"; values.len()].join(", ")
+                );
+                query.params.extend(values);
+            }
+            WhereClause::RowLt(columns, values) => {
+                query.sql = format!(
+                    "({}) < ({})",
+                    columns.join(", "),
+                    vec!["?"; values.len()].join(", ")
+                );
+                query.params.extend(values);
+            }
+
+            WhereClause::Between(column, low, high) => {
+                query.params.extend([low, high]);
+                query.sql = format!("{} BETWEEN ? AND ?", column);
+            }
+            WhereClause::ILike(column, value) => {
+                query.params.push(value);
+                query.sql = format!("lower({}) LIKE lower(?)", column);
+            }
+            WhereClause::Glob(column, value) => {
+                query.params.push(value);
+                query.sql = format!("{} GLOB ?", column);
+            }
+            // Uses regexp function registered by the backend
+            WhereClause::Regexp(column, value) => {
+                query.params.push(value);
+                query.sql = format!("{} REGEXP ?", column);
+            }
+            WhereClause::Exists(subquery) => {
+                let subquery = SqliteDialect::subquery(*subquery);
+                query.params.extend(subquery.params);
+                query.sql = format!("EXISTS ({})", subquery.sql);
+            }
+            WhereClause::CompareColumns(left, op, right) => {
+                query.sql = format!("{} {} {}", left, SqliteDialect::compare_op(op), right);
+            }
+            WhereClause::InSubquery(column, subquery) => {
+                let subquery = SqliteDialect::subquery(*subquery);
+                query.params.extend(subquery.params);
+                query.sql = format!("{} IN ({})", column, subquery.sql);
+            }
+            WhereClause::NotInSubquery(column, subquery) => {
+                let subquery = SqliteDialect::subquery(*subquery);
+                query.params.extend(subquery.params);
+                query.sql = format!("{} NOT IN ({})", column, subquery.sql);
+            }
+            WhereClause::CompareSubquery(column, op, subquery) => {
+                let subquery = SqliteDialect::subquery(*subquery);
+                query.params.extend(subquery.params);
+                query.sql = format!(
+                    "{} {} ({})",
+                    column,
+                    SqliteDialect::compare_op(op),
+                    subquery.sql
+                );
+            }
+
+            WhereClause::Compare(left, op, right) => {
+                let left = SqliteDialect::translate_expr(left);
+                let right = SqliteDialect::translate_expr(right);
+                query.params.extend(left.params);
+                query.params.extend(right.params);
+                query.sql = format!(
+                    "{} {} {}",
+                    left.sql,
+                    SqliteDialect::compare_op(op),
+                    right.sql
+                );
             }
 
             WhereClause::All => {
@@ -133,17 +237,101 @@ impl Dialect for SqliteDialect {
         query
     }
 
+    // ====< Translate projection to SQLite result column >====
+    fn translate_projection(projection: Projection) -> Query {
+        match projection {
+            Projection::Column(column) => Query::without_params(column),
+            Projection::ColumnAs(column, alias) => {
+                Query::without_params(format!("{} AS {}", column, alias))
+            }
+            Projection::Subquery(subquery, alias) => {
+                let subquery = SqliteDialect::subquery(*subquery);
+                Query::new(format!("({}) AS {}", subquery.sql, alias), subquery.params)
+            }
+            Projection::Expr(expr, alias) => {
+                let expr = SqliteDialect::translate_expr(expr);
+                Query::new(format!("{} AS {}", expr.sql, alias), expr.params)
+            }
+        }
+    }
+
+    // ====< Translate expression to SQLite expression >====
+    fn translate_expr(expr: Expr) -> Query {
+        match expr {
+            Expr::Column(column) => Query::without_params(column),
+            Expr::Value(value) => Query::new("?".to_string(), vec![value]),
+            Expr::Binary(left, op, right) => {
+                let left = SqliteDialect::translate_expr(*left);
+                let right = SqliteDialect::translate_expr(*right);
+                let op = match op {
+                    BinaryOp::Add => "+",
+                    BinaryOp::Sub => "-",
+                    BinaryOp::Mul => "*",
+                    BinaryOp::Div => "/",
+                    BinaryOp::Mod => "%",
+                    BinaryOp::Concat => "||",
+                };
+                Query::new(
+                    format!("({} {} {})", left.sql, op, right.sql),
+                    [left.params, right.params].concat(),
+                )
+            }
+            Expr::Function(name, args) => {
+                let mut query = Query::empty();
+                let args = args
+                    .into_iter()
+                    .map(|arg| {
+                        let arg = SqliteDialect::translate_expr(arg);
+                        query.params.extend(arg.params);
+                        arg.sql
+                    })
+                    .collect::<Vec<String>>();
+                query.sql = format!("{}({})", name, args.join(", "));
+                query
+            }
+            Expr::Cast(expr, data_type) => {
+                let mut query = SqliteDialect::translate_expr(*expr);
+                query.sql = format!(
+                    "CAST({} AS {})",
+                    query.sql,
+                    SqliteDialect::translate_type(data_type)
+                );
+                query
+            }
+            Expr::Case(branches, otherwise) => {
+                let mut query = Query::without_params("CASE".to_string());
+                for (condition, then) in branches {
+                    let condition = SqliteDialect::translate_where_clause(condition);
+                    let then = SqliteDialect::translate_expr(then);
+                    query
+                        .sql
+                        .push_str(&format!(" WHEN {} THEN {}", condition.sql, then.sql));
+                    query.params.extend(condition.params);
+                    query.params.extend(then.params);
+                }
+                if let Some(otherwise) = otherwise {
+                    let otherwise = SqliteDialect::translate_expr(*otherwise);
+                    query.sql.push_str(&format!(" ELSE {}", otherwise.sql));
+                    query.params.extend(otherwise.params);
+                }
+                query.sql.push_str(" END");
+                query
+            }
+            // Window functions are supported since SQLite 3.25
+            Expr::Window(function, window) => {
+                let mut query = SqliteDialect::translate_expr(*function);
+                let window = SqliteDialect::window_definition(window);
+                query.sql = format!("{} OVER ({})", query.sql, window.sql);
+                query.params.extend(window.params);
+                query
+            }
+        }
+    }
+
     // ====< Translate OrderBy to SQLite ORDER BY clause >====
     fn translate_order_by(order_by: OrderBy) -> String {
-        match order_by {
-            OrderBy::Asc(column) => format!("{} ASC", column),
-            OrderBy::Desc(column) => format!("{} DESC", column),
-            #[allow(unreachable_patterns)]
-            _ => unimplemented!(
-                "OrderBy {:?} is not implemented for SQLite dialect",
-                order_by
-            ),
-        }
+        // NULLS FIRST/LAST is supported since SQLite 3.30
+        SqliteDialect::order_by_term(order_by, rusqlite::version_number() >= 3_030_000)
     }
 
     // ====< Create table >====
@@ -169,6 +357,19 @@ impl Dialect for SqliteDialect {
                     .join(" ")
             ));
 
+            // SQLite has no enum type, so restrict allowed values
+            if let EzqlType::Enum { variants, .. } = &column.data_type {
+                sql.push_str(&format!(
+                    " CHECK({} IN ({}))",
+                    column.name,
+                    variants
+                        .iter()
+                        .map(|v| SqliteDialect::translate_value(EzqlValue::Enum(v.clone())))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                ));
+            }
+
             if i < table.columns.len() - 1 {
                 sql.push_str(", ");
             }
@@ -196,97 +397,176 @@ impl Dialect for SqliteDialect {
 
     // ====< Insert into table >====
     fn insert(table: &Table, models: Vec<Vec<Option<EzqlValue>>>) -> Query {
-        // Create insert keyword
-        let mut sql = format!("INSERT INTO {} (", table.name);
-
         // Create params
         let mut params = Vec::new();
 
-        // Add columns
-        for (i, column) in table.columns.iter().enumerate() {
-            // If column is primary key, skip it
-            if column.is_primary_key() {
-                continue;
-            }
+        // Generated columns can not be written and primary keys are
+        // skipped only if no row specifies them, so every row has the same shape
+        let columns = table
+            .columns
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| !c.is_generated())
+            .filter(|(i, c)| {
+                !c.is_primary_key() || models.iter().any(|m| matches!(m.get(*i), Some(Some(_))))
+            })
+            .collect::<Vec<_>>();
 
-            // If column is not specified, skip it
-            sql.push_str(&column.name);
+        // Add values
+        let mut rows = Vec::new();
+        for model in models.iter() {
+            for (i, column) in columns.iter() {
+                // If value is not specified use default or NULL
+                let value = match model.get(*i).cloned().flatten() {
+                    Some(value) => value,
+                    None => column.get_default().unwrap_or(EzqlValue::Null()),
+                };
 
-            if i < table.columns.len() - 1 {
-                sql.push_str(", ");
+                // Add value to params
+                params.push(value);
             }
+            rows.push(format!("({})", vec!["?"; columns.len()].join(", ")));
         }
 
-        // Add values
-        sql.push_str(") VALUES (");
-        for model in models.iter() {
-            for (i, value) in model.iter().enumerate() {
-                // If value is not specified NULL or DEFAULT
-                if value.is_none() {
-                    // If column is primary key, skip it
-                    if table.columns[i].is_primary_key() {
-                        continue;
-                    }
+        // Create insert query
+        let sql = format!(
+            "INSERT INTO {} ({}) VALUES {};",
+            table.name,
+            columns
+                .iter()
+                .map(|(_, c)| c.name.clone())
+                .collect::<Vec<String>>()
+                .join(", "),
+            rows.join(", ")
+        );
 
-                    // If column has default value, use it
-                    if table.columns[i].has_default() {
-                        sql.push_str(&SqliteDialect::translate_value(
-                            table.columns[i].get_default().unwrap(),
-                        ));
-                    } else {
-                        sql.push_str("NULL");
-                    }
+        // Return query
+        Query::new(sql, params)
+    }
 
-                    if i < model.len() - 1 {
-                        sql.push_str(", ");
-                    }
+    // ====< Maximum number of bound parameters per query >====
+    fn max_params() -> usize {
+        // Lowest default of SQLITE_MAX_VARIABLE_NUMBER across SQLite versions
+        999
+    }
 
-                    continue;
-                }
+    // ====< Insert or update on conflict >====
+    fn upsert(
+        table: &Table,
+        models: Vec<Vec<Option<EzqlValue>>>,
+        conflict: Vec<String>,
+        on_conflict: OnConflict,
+    ) -> Query {
+        // Start with plain insert without semicolon
+        let mut query = SqliteDialect::insert(table, models);
+        query.sql.pop();
 
-                // Add value to params
-                params.push(value.clone().unwrap());
+        // Add conflict target, any constraint conflicts without one
+        if conflict.is_empty() {
+            query.sql.push_str(" ON CONFLICT");
+        } else {
+            query
+                .sql
+                .push_str(&format!(" ON CONFLICT ({})", conflict.join(", ")));
+        }
+
+        // Columns to overwrite with inserted values
+        let overwrite = match &on_conflict {
+            OnConflict::UpdateAll => table
+                .columns
+                .iter()
+                .filter(|c| !c.is_primary_key() && !c.is_generated())
+                .filter(|c| !conflict.contains(&c.name))
+                .map(|c| c.name.clone())
+                .collect(),
+            OnConflict::Update(columns) => columns.clone(),
+            _ => vec![],
+        };
 
-                // Add value to query
-                sql.push('?');
+        match on_conflict {
+            OnConflict::Set(update) => {
+                // Set columns
+                let set = SqliteDialect::assignments(update.set);
+                query.sql.push_str(&format!(" DO UPDATE SET {}", set.sql));
+                query.params.extend(set.params);
 
-                if i < model.len() - 1 {
-                    sql.push_str(", ");
+                // Add where clause
+                if let Some(where_clause) = update.where_clause {
+                    let where_clause = SqliteDialect::translate_where_clause(where_clause);
+                    query.params.extend(where_clause.params);
+                    query.sql.push_str(&format!(" WHERE {}", where_clause.sql));
                 }
             }
-
-            sql.push_str("), (");
+            _ if overwrite.is_empty() => query.sql.push_str(" DO NOTHING"),
+            _ => query.sql.push_str(&format!(
+                " DO UPDATE SET {}",
+                overwrite
+                    .iter()
+                    .map(|column| format!("{} = excluded.{}", column, column))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )),
         }
 
-        // Remove empty parentheses and comma
-        sql.pop();
-        sql.pop();
-        sql.pop();
-
         // End query with semicolon
-        sql.push(';');
+        query.sql.push(';');
 
         // Return query
-        Query::new(sql, params)
+        query
+    }
+
+    // ====< Return rows affected by query >====
+    fn returning(query: Query, columns: Option<Vec<String>>) -> Query {
+        let mut query = query;
+
+        // Remove semicolon
+        if query.sql.ends_with(';') {
+            query.sql.pop();
+        }
+
+        // Add returning clause
+        query.sql.push_str(&format!(
+            " RETURNING {};",
+            columns
+                .map(|c| c.join(", "))
+                .unwrap_or_else(|| "*".to_string())
+        ));
+
+        // Return query
+        query
     }
 
     // ====< Select from table >====
     fn select(table: &Table, query_params: SelectQueryParams) -> Query {
-        // Create select keyword
-        let mut sql = "SELECT ".to_string();
+        // SQLite has no DISTINCT ON
+        if let Some(Distinct::On(exprs)) = &query_params.distinct {
+            return SqliteDialect::distinct_on(table, query_params.clone(), exprs.clone());
+        }
+
+        // Add common table expressions
+        let with = SqliteDialect::with_clause(query_params.with);
+        let mut sql = with.sql;
 
         // Create params
-        let mut params = Vec::new();
+        let mut params = with.params;
+
+        // Create select keyword
+        sql.push_str("SELECT ");
+        if query_params.distinct.is_some() {
+            sql.push_str("DISTINCT ");
+        }
 
-        // Add columns from select query params
+        // Add columns from select query params, subquery params come first
         if let Some(select_columns) = query_params.columns {
-            sql.push_str(
-                &select_columns
-                    .iter()
-                    .map(|c| c.to_string())
-                    .collect::<Vec<String>>()
-                    .join(", "),
-            );
+            let columns = select_columns
+                .into_iter()
+                .map(|c| {
+                    let column = SqliteDialect::translate_projection(c);
+                    params.extend(column.params);
+                    column.sql
+                })
+                .collect::<Vec<String>>();
+            sql.push_str(&columns.join(", "));
         } else {
             sql.push('*');
         }
@@ -294,6 +574,11 @@ impl Dialect for SqliteDialect {
         // Add from keyword
         sql.push_str(&format!(" FROM {}", table.name));
 
+        // Add joins
+        let joins = SqliteDialect::join_clauses(query_params.joins);
+        sql.push_str(&joins.sql);
+        params.extend(joins.params);
+
         // Add where clause
         if let Some(where_clause) = query_params.where_clause {
             let where_clause = SqliteDialect::translate_where_clause(where_clause);
@@ -302,10 +587,15 @@ impl Dialect for SqliteDialect {
         }
 
         // Add order by clause
-        if let Some(order_by) = query_params.order_by {
+        if !query_params.order_by.is_empty() {
             sql.push_str(&format!(
                 " ORDER BY {}",
-                SqliteDialect::translate_order_by(order_by)
+                query_params
+                    .order_by
+                    .into_iter()
+                    .map(SqliteDialect::translate_order_by)
+                    .collect::<Vec<String>>()
+                    .join(", ")
             ));
         }
 
@@ -326,13 +616,114 @@ impl Dialect for SqliteDialect {
         Query::new(sql, params)
     }
 
+    // ====< Count rows in table >====
+    fn count(table: &Table, query_params: SelectQueryParams) -> Query {
+        // Add common table expressions
+        let with = SqliteDialect::with_clause(query_params.with);
+        let mut sql = with.sql;
+
+        // Create params
+        let mut params = with.params;
+
+        // Count distinct rows from select without order by, limit and offset
+        if query_params.distinct.is_some() {
+            let select = SqliteDialect::select(
+                table,
+                SelectQueryParams {
+                    order_by: vec![],
+                    limit: None,
+                    offset: None,
+                    with: vec![],
+                    ..query_params
+                },
+            );
+            sql.push_str(&format!(
+                "SELECT COUNT(*) FROM ({});",
+                select.sql.trim_end_matches(';')
+            ));
+            params.extend(select.params);
+            return Query::new(sql, params);
+        }
+
+        // Create count keyword
+        sql.push_str(&format!("SELECT COUNT(*) FROM {}", table.name));
+
+        // Add joins
+        let joins = SqliteDialect::join_clauses(query_params.joins);
+        sql.push_str(&joins.sql);
+        params.extend(joins.params);
+
+        // Add where clause
+        if let Some(where_clause) = query_params.where_clause {
+            let where_clause = SqliteDialect::translate_where_clause(where_clause);
+            params.extend(where_clause.params);
+            sql.push_str(&format!(" WHERE {}", where_clause.sql));
+        }
+
+        // Ignore order by, limit and offset
+
+        // End query with semicolon
+        sql.push(';');
+
+        // Return query
+        Query::new(sql, params)
+    }
+
+    // ====< Compound select >====
+    fn compound_select(compound: CompoundSelect) -> Query {
+        let mut query = SqliteDialect::compound_member(compound.first);
+        for (op, member) in compound.rest {
+            let member = SqliteDialect::compound_member(member);
+            let op = match op {
+                CompoundOp::Union => "UNION",
+                CompoundOp::UnionAll => "UNION ALL",
+                CompoundOp::Intersect => "INTERSECT",
+                CompoundOp::Except => "EXCEPT",
+            };
+            query.sql.push_str(&format!(" {} {}", op, member.sql));
+            query.params.extend(member.params);
+        }
+
+        // Add order by clause
+        if !compound.order_by.is_empty() {
+            query.sql.push_str(&format!(
+                " ORDER BY {}",
+                compound
+                    .order_by
+                    .into_iter()
+                    .map(SqliteDialect::translate_order_by)
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+        }
+
+        // Add limit clause
+        if let Some(limit) = compound.limit {
+            query.sql.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        // Add offset clause
+        if let Some(offset) = compound.offset {
+            query.sql.push_str(&format!(" OFFSET {}", offset));
+        }
+
+        // End query with semicolon
+        query.sql.push(';');
+
+        query
+    }
+
     // ====< Delete from table >====
     fn delete(table: &Table, query_params: SelectQueryParams) -> Query {
-        // Create delete keyword
-        let mut sql = format!("DELETE FROM {}", table.name);
+        // Add common table expressions
+        let with = SqliteDialect::with_clause(query_params.with);
+        let mut sql = with.sql;
 
         // Create params
-        let mut params = Vec::new();
+        let mut params = with.params;
+
+        // Create delete keyword
+        sql.push_str(&format!("DELETE FROM {}", table.name));
 
         // Add where clause
         if let Some(where_clause) = query_params.where_clause {
@@ -341,7 +732,7 @@ impl Dialect for SqliteDialect {
             sql.push_str(&format!(" WHERE {}", where_clause.sql));
         }
 
-        // Ignore order by, limit and offset
+        // Ignore joins, order by, limit and offset
 
         // End query with semicolon
         sql.push(';');
@@ -358,15 +749,21 @@ impl Dialect for SqliteDialect {
         // Create params
         let mut params = Vec::new();
 
-        // Set columns
-        for column in query_params.set.iter() {
-            sql.push_str(&format!("{} = ?, ", column.0));
-            params.push(column.1.clone());
-        }
-
-        // Remove comma
-        sql.pop();
-        sql.pop();
+        // Set columns, generated columns can not be written
+        let set = SqliteDialect::assignments(
+            query_params
+                .set
+                .into_iter()
+                .filter(|(name, _)| {
+                    !table
+                        .columns
+                        .iter()
+                        .any(|c| c.name == *name && c.is_generated())
+                })
+                .collect(),
+        );
+        sql.push_str(&set.sql);
+        params.extend(set.params);
 
         // Add where clause
         if let Some(where_clause) = query_params.where_clause {
@@ -386,3 +783,345 @@ impl Dialect for SqliteDialect {
 }
 
 // ====< Impl >====
+impl SqliteDialect {
+    // ====< Emulate DISTINCT ON with row numbers per group >====
+    fn distinct_on(table: &Table, query_params: SelectQueryParams, exprs: Vec<Expr>) -> Query {
+        // Number rows of each group in query order
+        let row_number = Expr::row_number().over(Window {
+            partition_by: exprs,
+            order_by: query_params.order_by.clone(),
+            frame: None,
+        });
+        let inner = SqliteDialect::subquery(Subquery::new(
+            table.clone(),
+            SelectQueryParams {
+                columns: Some(vec![
+                    "*".into(),
+                    Projection::Expr(row_number, "ezql_distinct_row".to_string()),
+                ]),
+                where_clause: query_params.where_clause,
+                joins: query_params.joins,
+                ..SelectQueryParams::default()
+            },
+        ));
+
+        // Keep first row of each group, subquery is named like the table
+        // so qualified column names still work
+        let columns = query_params.columns.unwrap_or_else(|| {
+            table
+                .columns
+                .iter()
+                .map(|c| Projection::Column(c.name.clone()))
+                .collect()
+        });
+        let mut query = SqliteDialect::select(
+            &Table {
+                name: format!("({}) AS {}", inner.sql, table.name),
+                columns: table.columns.clone(),
+            },
+            SelectQueryParams {
+                columns: if columns.is_empty() {
+                    None
+                } else {
+                    Some(columns)
+                },
+                where_clause: Some(WhereClause::CompareColumns(
+                    "ezql_distinct_row".to_string(),
+                    CompareOp::Eq,
+                    "1".to_string(),
+                )),
+                order_by: query_params.order_by,
+                limit: query_params.limit,
+                offset: query_params.offset,
+                with: query_params.with,
+                ..SelectQueryParams::default()
+            },
+        );
+
+        // Only CTEs and projection have parameters before the subquery
+        query.params.extend(inner.params);
+        query
+    }
+
+    // ====< Translate compound select member >====
+    fn compound_member(member: Subquery) -> Query {
+        // SQLite does not allow ORDER BY, LIMIT or WITH on members,
+        // so such members are selected from a subquery
+        let wrap = !member.query.order_by.is_empty()
+            || member.query.limit.is_some()
+            || member.query.offset.is_some()
+            || !member.query.with.is_empty();
+        let mut query = SqliteDialect::subquery(member);
+        if wrap {
+            query.sql = format!("SELECT * FROM ({})", query.sql);
+        }
+        query
+    }
+
+    // ====< Translate window definition >====
+    fn window_definition(window: Window) -> Query {
+        let mut query = Query::empty();
+        let mut parts = Vec::new();
+
+        if !window.partition_by.is_empty() {
+            let partition_by = window
+                .partition_by
+                .into_iter()
+                .map(|expr| {
+                    let expr = SqliteDialect::translate_expr(expr);
+                    query.params.extend(expr.params);
+                    expr.sql
+                })
+                .collect::<Vec<String>>();
+            parts.push(format!("PARTITION BY {}", partition_by.join(", ")));
+        }
+        if !window.order_by.is_empty() {
+            parts.push(format!(
+                "ORDER BY {}",
+                window
+                    .order_by
+                    .into_iter()
+                    .map(SqliteDialect::translate_order_by)
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+        }
+        if let Some(frame) = window.frame {
+            let bound = |bound: FrameBound| match bound {
+                FrameBound::UnboundedPreceding => "UNBOUNDED PRECEDING".to_string(),
+                FrameBound::Preceding(n) => format!("{} PRECEDING", n),
+                FrameBound::CurrentRow => "CURRENT ROW".to_string(),
+                FrameBound::Following(n) => format!("{} FOLLOWING", n),
+                FrameBound::UnboundedFollowing => "UNBOUNDED FOLLOWING".to_string(),
+            };
+            parts.push(format!(
+                "{} BETWEEN {} AND {}",
+                match frame.units {
+                    FrameUnits::Rows => "ROWS",
+                    FrameUnits::Range => "RANGE",
+                    FrameUnits::Groups => "GROUPS",
+                },
+                bound(frame.start),
+                bound(frame.end)
+            ));
+        }
+
+        query.sql = parts.join(" ");
+        query
+    }
+
+    // ====< Translate WITH clause, empty if there are no CTEs >====
+    fn with_clause(ctes: Vec<Cte>) -> Query {
+        let mut query = Query::empty();
+        if ctes.is_empty() {
+            return query;
+        }
+
+        let recursive = ctes.iter().any(|cte| cte.recursive.is_some());
+        let ctes = ctes
+            .into_iter()
+            .map(|cte| {
+                let columns = if cte.columns.is_empty() {
+                    String::new()
+                } else {
+                    format!("({})", cte.columns.join(", "))
+                };
+                let mut body = SqliteDialect::subquery(cte.query);
+                if let Some(recursive) = cte.recursive {
+                    let recursive = SqliteDialect::subquery(recursive);
+                    body.sql = format!("{} UNION ALL {}", body.sql, recursive.sql);
+                    body.params.extend(recursive.params);
+                }
+                query.params.extend(body.params);
+                format!("{}{} AS ({})", cte.name, columns, body.sql)
+            })
+            .collect::<Vec<String>>();
+
+        query.sql = format!(
+            "WITH {}{} ",
+            if recursive { "RECURSIVE " } else { "" },
+            ctes.join(", ")
+        );
+        query
+    }
+
+    // ====< Translate joins, each with leading space >====
+    fn join_clauses(joins: Vec<Join>) -> Query {
+        let mut query = Query::empty();
+        for join in joins {
+            let kind = match join.kind {
+                JoinKind::Inner => "INNER JOIN",
+                JoinKind::Left => "LEFT JOIN",
+                JoinKind::Cross => "CROSS JOIN",
+            };
+            query.sql.push_str(&format!(" {} {}", kind, join.table));
+            if let Some(on) = join.on {
+                let on = SqliteDialect::translate_where_clause(on);
+                query.sql.push_str(&format!(" ON {}", on.sql));
+                query.params.extend(on.params);
+            }
+        }
+        query
+    }
+
+    // ====< Translate SET assignments, column = expr >====
+    fn assignments(set: Vec<(String, Expr)>) -> Query {
+        let mut query = Query::empty();
+        let assignments = set
+            .into_iter()
+            .map(|(column, expr)| {
+                let expr = SqliteDialect::translate_expr(expr);
+                query.params.extend(expr.params);
+                format!("{} = {}", column, expr.sql)
+            })
+            .collect::<Vec<String>>();
+        query.sql = assignments.join(", ");
+        query
+    }
+
+    fn compare_op(op: CompareOp) -> &'static str {
+        match op {
+            CompareOp::Eq => "=",
+            CompareOp::Ne => "!=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+        }
+    }
+
+    // ====< Translate nested select, without trailing semicolon >====
+    pub(crate) fn subquery(subquery: Subquery) -> Query {
+        let mut query = SqliteDialect::select(&subquery.table, subquery.query);
+        query.sql = query.sql.trim_end_matches(';').to_string();
+        query
+    }
+
+    // ====< Translate OrderBy, emulating NULLS FIRST/LAST if not supported >====
+    pub(crate) fn order_by_term(order_by: OrderBy, native_nulls: bool) -> String {
+        let mut term = order_by.expr.clone();
+        if let Some(collation) = &order_by.collate {
+            term.push_str(&format!(" COLLATE {}", collation));
+        }
+        term.push_str(match order_by.direction {
+            Direction::Asc => " ASC",
+            Direction::Desc => " DESC",
+        });
+
+        match order_by.nulls {
+            Some(nulls) if native_nulls => {
+                term.push_str(match nulls {
+                    Nulls::First => " NULLS FIRST",
+                    Nulls::Last => " NULLS LAST",
+                });
+                term
+            }
+            // Sort on NULL check first, `expr IS NULL` is 1 for NULL values
+            Some(nulls) => format!(
+                "({}) IS NULL {}, {}",
+                order_by.expr,
+                match nulls {
+                    Nulls::First => "DESC",
+                    Nulls::Last => "ASC",
+                },
+                term
+            ),
+            None => term,
+        }
+    }
+
+    // ====< Translate SQLite declared type to EzqlType >====
+    // Follows SQLite type affinity rules, REAL and BLOB have no EzqlType
+    pub fn parse_type(declared: &str) -> Option<EzqlType> {
+        let declared = declared.trim().to_ascii_uppercase();
+        if declared.starts_with("BOOL") {
+            return Some(EzqlType::Boolean());
+        }
+        if declared.contains("INT") {
+            return Some(EzqlType::Integer());
+        }
+        if ["CHAR", "CLOB", "TEXT"]
+            .iter()
+            .any(|t| declared.contains(t))
+        {
+            // Length is not enforced by SQLite, text without one is VarChar(0)
+            let length = match (declared.find('('), declared.rfind(')')) {
+                (Some(start), Some(end)) if start < end => {
+                    declared[start + 1..end].trim().parse().ok()?
+                }
+                _ => 0,
+            };
+            return Some(EzqlType::VarChar(length));
+        }
+        None
+    }
+
+    // ====< Translate SQLite literal to EzqlValue >====
+    pub fn parse_value(literal: &str) -> Option<EzqlValue> {
+        let literal = literal.trim();
+        if let Some(text) = literal
+            .strip_prefix('\'')
+            .and_then(|text| text.strip_suffix('\''))
+        {
+            return Some(EzqlValue::VarChar(text.replace("''", "'")));
+        }
+        match literal.to_ascii_uppercase().as_str() {
+            "NULL" => Some(EzqlValue::Null()),
+            "TRUE" => Some(EzqlValue::Boolean(true)),
+            "FALSE" => Some(EzqlValue::Boolean(false)),
+            _ => literal.parse().ok().map(EzqlValue::Integer),
+        }
+    }
+
+    // ====< Split CREATE TABLE statement into column definitions >====
+    pub fn column_definitions(create_sql: &str) -> Vec<(String, String)> {
+        let body = match (create_sql.find('('), create_sql.rfind(')')) {
+            (Some(start), Some(end)) if start < end => &create_sql[start + 1..end],
+            _ => return vec![],
+        };
+
+        split_top_level(body, ',')
+            .into_iter()
+            .filter_map(|definition| {
+                let definition = definition.trim();
+                let (name, rest) = definition
+                    .split_once(char::is_whitespace)
+                    .unwrap_or((definition, ""));
+
+                // Skip table constraints
+                if ["PRIMARY", "UNIQUE", "CHECK", "FOREIGN", "CONSTRAINT"]
+                    .contains(&name.to_ascii_uppercase().as_str())
+                {
+                    return None;
+                }
+
+                Some((
+                    name.trim_matches(|c| c == '"' || c == '`').to_string(),
+                    rest.trim().to_string(),
+                ))
+            })
+            .collect()
+    }
+
+    // ====< Find parenthesized expressions following keyword >====
+    pub fn clause_expressions(definition: &str, keyword: &str) -> Vec<String> {
+        let upper = definition.to_ascii_uppercase();
+        let mut expressions = Vec::new();
+        let mut offset = 0;
+        while let Some(found) = upper[offset..].find(keyword) {
+            let start = offset + found + keyword.len();
+            let open = match definition[start..].find('(') {
+                Some(open) => start + open,
+                None => break,
+            };
+            match parenthesized(definition, open) {
+                Some(expr) => {
+                    expressions.push(expr.trim().to_string());
+                    offset = open + expr.len() + 2;
+                }
+                None => break,
+            }
+        }
+        expressions
+    }
+}
@@ -4,25 +4,67 @@ pub mod dialects;
 pub mod types;
 mod utils;
 
+#[cfg(feature = "async")]
+pub use crate::backends::async_backend::{AsyncBackend, AsyncError};
 pub use crate::backends::Backend;
+pub use crate::backends::BulkInsertProgress;
 
 pub mod queries {
+    pub use crate::components::expr::BinaryOp;
+    pub use crate::components::expr::CaseBuilder;
+    pub use crate::components::expr::Expr;
+    pub use crate::components::expr::Frame;
+    pub use crate::components::expr::FrameBound;
+    pub use crate::components::expr::FrameUnits;
+    pub use crate::components::expr::Window;
+    pub use crate::components::pagination::Cursor;
+    pub use crate::components::pagination::Keyset;
+    pub use crate::components::pagination::KeysetPage;
+    pub use crate::components::pagination::Page;
+    pub use crate::components::query::CompareOp;
+    pub use crate::components::query::CompoundOp;
+    pub use crate::components::query::CompoundSelect;
+    pub use crate::components::query::Cte;
+    pub use crate::components::query::Direction;
+    pub use crate::components::query::Distinct;
+    pub use crate::components::query::Join;
+    pub use crate::components::query::JoinKind;
+    pub use crate::components::query::Nulls;
+    pub use crate::components::query::OnConflict;
     pub use crate::components::query::OrderBy;
+    pub use crate::components::query::Projection;
     pub use crate::components::query::Query;
+    pub use crate::components::query::RawParams;
     pub use crate::components::query::SelectQueryParams;
+    pub use crate::components::query::Subquery;
     pub use crate::components::query::UpdateQueryParams;
     pub use crate::components::query::WhereClause;
 }
 
 pub mod prelude {
+    #[cfg(feature = "async")]
+    pub use crate::backends::async_backend::AsyncModelBackend;
     pub use crate::backends::ModelBackend;
     pub use crate::components::column::Column;
     pub use crate::components::column::ColumnProperty;
+    pub use crate::components::model::decode_column;
+    pub use crate::components::model::DecodeError;
     pub use crate::components::model::EzqlModelTrait;
+    pub use crate::components::model::EzqlRecordTrait;
+    pub use crate::components::row::Row;
+    pub use crate::components::row::RowColumn;
     pub use crate::components::table::Table;
     pub use crate::types::EzqlType;
     pub use crate::types::EzqlValue;
+    pub use crate::types::FromEzql;
+    pub use crate::types::ToEzql;
 }
 
+#[cfg(all(feature = "sqlite", feature = "async"))]
+pub use backends::sqlite_async::AsyncSqliteBackend;
 #[cfg(feature = "sqlite")]
-pub use backends::sqlite_backend::SqliteBackend;
+pub use backends::sqlite_backend::{SqliteBackend, StatementCacheStats};
+#[cfg(feature = "sqlite")]
+pub use backends::sqlite_pool::{PooledConnection, SqlitePool, SqlitePoolOptions};
+#[cfg(feature = "sqlite")]
+pub use backends::sqlite_stream::{SelectBatches, SelectIter, SelectStatement};
@@ -0,0 +1,102 @@
+use super::ezql_types::{EzqlType, EzqlValue};
+
+// ====< Trait for rust types that can be written to a column >====
+pub trait ToEzql {
+    fn ezql_type() -> EzqlType;
+    fn to_ezql(&self) -> EzqlValue;
+
+    // Whether the column can hold NULL (only true for Option<T>)
+    fn nullable() -> bool {
+        false
+    }
+}
+
+// ====< Trait for rust types that can be read from a column >====
+pub trait FromEzql: Sized {
+    fn from_ezql(value: EzqlValue) -> Result<Self, ConversionError>;
+}
+
+// ====< Nullable type mappings >====
+impl<T> ToEzql for Option<T>
+where
+    T: ToEzql,
+{
+    fn ezql_type() -> EzqlType {
+        T::ezql_type()
+    }
+
+    fn to_ezql(&self) -> EzqlValue {
+        match self {
+            Some(value) => value.to_ezql(),
+            None => EzqlValue::Null(),
+        }
+    }
+
+    fn nullable() -> bool {
+        true
+    }
+}
+
+impl<T> FromEzql for Option<T>
+where
+    T: FromEzql,
+{
+    fn from_ezql(value: EzqlValue) -> Result<Self, ConversionError> {
+        match value {
+            EzqlValue::Null() => Ok(None),
+            value => T::from_ezql(value).map(Some),
+        }
+    }
+}
+
+impl<T> From<Option<T>> for EzqlValue
+where
+    T: ToEzql,
+{
+    fn from(value: Option<T>) -> Self {
+        value.to_ezql()
+    }
+}
+
+// ====< Conversion error >====
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionError {
+    pub expected: String,
+    pub actual: EzqlValue,
+    // Index of the column in the table, if known
+    pub column: Option<usize>,
+}
+
+impl ConversionError {
+    pub fn new(expected: &str, actual: EzqlValue) -> Self {
+        Self {
+            expected: expected.to_string(),
+            actual,
+            column: None,
+        }
+    }
+
+    pub fn at_column(mut self, column: usize) -> Self {
+        self.column = Some(column);
+        self
+    }
+
+    pub fn actual_type(&self) -> &'static str {
+        self.actual.type_name()
+    }
+}
+
+// ====< Pretty print conversion error >====
+impl std::fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Cannot convert {} value {:?} to {}",
+            self.actual_type(),
+            self.actual,
+            self.expected
+        )
+    }
+}
+
+impl std::error::Error for ConversionError {}
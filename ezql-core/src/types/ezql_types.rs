@@ -10,6 +10,14 @@ macro_rules! create_types {
         pub enum EzqlValue {
             $($name $argV),*
         }
+
+        impl EzqlValue {
+            pub fn type_name(&self) -> &'static str {
+                match self {
+                    $(EzqlValue::$name { .. } => stringify!($name)),*
+                }
+            }
+        }
     }
 }
 
@@ -19,6 +27,7 @@ create_types! {
     Integer () => (i32),
     VarChar (usize) => (String),
     Boolean () => (bool),
+    Enum { name: String, variants: Vec<String> } => (String),
     Null () => (),
 }
 
@@ -29,6 +38,15 @@ impl std::fmt::Display for EzqlType {
             EzqlType::Integer() => write!(f, "INTEGER"),
             EzqlType::VarChar(len) => write!(f, "VARCHAR({})", len),
             EzqlType::Boolean() => write!(f, "BOOLEAN"),
+            EzqlType::Enum { variants, .. } => write!(
+                f,
+                "ENUM({})",
+                variants
+                    .iter()
+                    .map(|v| format!("'{}'", v))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
             EzqlType::Null() => write!(f, "NULL"),
         }
     }
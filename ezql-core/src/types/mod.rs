@@ -1,4 +1,6 @@
+mod conversion;
 mod ezql_types;
 mod types_list;
 
+pub use conversion::{ConversionError, FromEzql, ToEzql};
 pub use ezql_types::{EzqlType, EzqlValue};
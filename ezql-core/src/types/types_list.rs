@@ -1,3 +1,4 @@
+use super::conversion::{ConversionError, FromEzql, ToEzql};
 use super::ezql_types::{EzqlType, EzqlValue};
 
 // ====< Macro for generating type mappings >====
@@ -5,15 +6,25 @@ use super::ezql_types::{EzqlType, EzqlValue};
 macro_rules! impl_ezql_types {
     ($($type:ty => $ezql_type:ident ($($argT:expr),*)),*,) => {
         $(
+            impl ToEzql for $type {
+                fn ezql_type() -> EzqlType {
+                    EzqlType::$ezql_type($($argT),*)
+                }
+
+                fn to_ezql(&self) -> EzqlValue {
+                    EzqlValue::$ezql_type(self.to_owned().into())
+                }
+            }
+
             impl From<$type> for EzqlType {
                 fn from(_: $type) -> Self {
-                    EzqlType::$ezql_type($($argT),*)
+                    <$type as ToEzql>::ezql_type()
                 }
             }
 
             impl From<$type> for EzqlValue {
                 fn from(value: $type) -> Self {
-                    EzqlValue::$ezql_type(value.into())
+                    value.to_ezql()
                 }
             }
         )*
@@ -25,33 +36,71 @@ macro_rules! impl_ezql_types {
 macro_rules! impl_ezql_types_inverse {
     ($($type:ty => ($($ezql_type:ident),*)),*,) => {
         $(
-            #[allow(clippy::from_over_into)]
-            impl Into<$type> for EzqlValue {
-                fn into(self) -> $type {
-                    match self {
+            impl FromEzql for $type {
+                fn from_ezql(value: EzqlValue) -> Result<Self, ConversionError> {
+                    match value {
                         $(
-                        EzqlValue::$ezql_type(v) => v.into()
+                        EzqlValue::$ezql_type(v) => Ok(v.into())
                         ),*,
-                        _ => panic!("Cannot convert {:?} to {}", self, stringify!($type)),
+                        _ => Err(ConversionError::new(stringify!($type), value)),
                     }
                 }
             }
 
-            #[allow(clippy::from_over_into)]
-            impl Into<$type> for &EzqlValue {
-                fn into(self) -> $type {
-                    match self {
-                        $(
-                            EzqlValue::$ezql_type(v) => (*v).clone().into()
-                        ),*,
-                        _ => panic!("Cannot convert {:?} to {}", self, stringify!($type)),
-                    }
+            impl TryFrom<EzqlValue> for $type {
+                type Error = ConversionError;
+
+                fn try_from(value: EzqlValue) -> Result<Self, Self::Error> {
+                    <$type>::from_ezql(value)
                 }
             }
         )*
     };
 }
 
+// ====< Macro for mapping rust enums to enum columns >====
+#[macro_export]
+macro_rules! impl_ezql_enum {
+    ($type:ident { $($variant:ident => $value:literal),*, }) => {
+        impl $crate::types::ToEzql for $type {
+            fn ezql_type() -> $crate::types::EzqlType {
+                $crate::types::EzqlType::Enum {
+                    name: stringify!($type).to_string(),
+                    variants: vec![$($value.to_string()),*],
+                }
+            }
+
+            fn to_ezql(&self) -> $crate::types::EzqlValue {
+                match self {
+                    $($type::$variant => $crate::types::EzqlValue::Enum($value.to_string())),*
+                }
+            }
+        }
+
+        impl $crate::types::FromEzql for $type {
+            fn from_ezql(
+                value: $crate::types::EzqlValue,
+            ) -> Result<Self, $crate::types::ConversionError> {
+                match &value {
+                    $crate::types::EzqlValue::Enum(v) | $crate::types::EzqlValue::VarChar(v) => {
+                        match v.as_str() {
+                            $($value => Ok($type::$variant)),*,
+                            _ => Err($crate::types::ConversionError::new(stringify!($type), value)),
+                        }
+                    }
+                    _ => Err($crate::types::ConversionError::new(stringify!($type), value)),
+                }
+            }
+        }
+
+        impl From<$type> for $crate::types::EzqlValue {
+            fn from(value: $type) -> Self {
+                $crate::types::ToEzql::to_ezql(&value)
+            }
+        }
+    };
+}
+
 // ====< Rust type mappings >====
 impl_ezql_types!(
     i32 => Integer(),
@@ -67,13 +116,20 @@ impl_ezql_types_inverse!(
 );
 
 // ====< Custom type mappings >====
-#[allow(clippy::from_over_into)]
-impl Into<bool> for &EzqlValue {
-    fn into(self) -> bool {
-        match self {
-            EzqlValue::Boolean(v) => *v,
-            EzqlValue::Integer(v) => *v != 0,
-            _ => panic!("Cannot convert {:?} to bool", self),
+impl FromEzql for bool {
+    fn from_ezql(value: EzqlValue) -> Result<Self, ConversionError> {
+        match value {
+            EzqlValue::Boolean(v) => Ok(v),
+            EzqlValue::Integer(v) => Ok(v != 0),
+            _ => Err(ConversionError::new("bool", value)),
         }
     }
 }
+
+impl TryFrom<EzqlValue> for bool {
+    type Error = ConversionError;
+
+    fn try_from(value: EzqlValue) -> Result<Self, Self::Error> {
+        bool::from_ezql(value)
+    }
+}
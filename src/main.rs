@@ -37,19 +37,19 @@ impl EzqlModelTrait for User {
 
     fn as_column_values(&self) -> Vec<Option<EzqlValue>> {
         vec![
-            self.id.map(EzqlValue::Integer),
-            self.name.as_ref().map(|v| EzqlValue::VarChar(v.clone())),
-            self.is_active.map(EzqlValue::Boolean),
+            self.id.as_ref().map(ToEzql::to_ezql),
+            self.name.as_ref().map(ToEzql::to_ezql),
+            self.is_active.as_ref().map(ToEzql::to_ezql),
         ]
     }
 
     fn from_column_values(
         values: Vec<Option<EzqlValue>>,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         Ok(Self {
-            id: values[0].as_ref().map(|v| v.into()),
-            name: values[1].as_ref().map(|v| v.into()),
-            is_active: values[2].as_ref().map(|v| v.into()),
+            id: decode_column(&values, 0)?,
+            name: decode_column(&values, 1)?,
+            is_active: decode_column(&values, 2)?,
         })
     }
 }
@@ -83,11 +83,14 @@ fn main() {
 
     // Select data
     let select_params = SelectQueryParams {
-        columns: Some(vec!["id".to_string(), "is_active".to_string()]),
+        columns: Some(vec!["id".into(), "is_active".into()]),
         where_clause: Some(WhereClause::Eq("name".to_string(), "John".into())),
-        order_by: Some(OrderBy::Desc("id".to_string())),
+        order_by: vec![OrderBy::desc("id")],
         limit: Some(4),
         offset: None,
+        joins: vec![],
+        with: vec![],
+        distinct: None,
     };
 
     println!(